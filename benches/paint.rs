@@ -0,0 +1,69 @@
+//! rough timings of tessellating and painting icons, run with `cargo bench`
+
+use egui_svgicon::Svg;
+use std::time::{Duration, Instant};
+
+/// average time of `f` over enough iterations to take about half a second
+fn bench(name: &str, mut f: impl FnMut()) {
+    f();
+    let (mut iterations, start) = (0u32, Instant::now());
+    while start.elapsed() < Duration::from_millis(500) {
+        f();
+        iterations += 1;
+    }
+    println!("{name:<40} {:>10.1?}", start.elapsed() / iterations);
+}
+
+/// a grid of circles, tens of thousands of vertices at large sizes
+fn detailed_svg() -> Svg {
+    let circles = (0..1600)
+        .map(|i| format!(r#"<circle cx="{}" cy="{}" r="0.4"/>"#, i % 40, i / 40))
+        .collect::<String>();
+    Svg::new_from_str(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 40">{circles}</svg>"#
+    ))
+}
+
+/// run one frame showing `svgs` at `size`
+fn frame(ctx: &egui::Context, svgs: &[Svg], size: f32) {
+    let _ = ctx.run(Default::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            for svg in svgs {
+                svg.clone().show_sized(ui, egui::Vec2::splat(size));
+            }
+        });
+    });
+}
+
+fn main() {
+    let svg = detailed_svg();
+    println!(
+        "detailed icon at 512: {} vertices",
+        svg.stats([512.0, 512.0]).vertices
+    );
+
+    bench("tessellate detailed icon", || {
+        svg.to_mesh_sized([512.0, 512.0]);
+    });
+    let ctx = egui::Context::default();
+    bench("paint detailed icon, cache hit", || {
+        frame(&ctx, std::slice::from_ref(&svg), 512.0)
+    });
+    let empty = egui::Context::default();
+    bench("empty frame", || frame(&empty, &[], 512.0));
+
+    // a toolbar of small icons
+    let toolbar = (0..100)
+        .map(|i| {
+            Svg::new_from_str(&format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><circle cx="8" cy="8" r="{}"/></svg>"#,
+                1.0 + i as f32 / 16.0
+            ))
+        })
+        .collect::<Vec<_>>();
+    bench("100 icons, first frame", || {
+        frame(&egui::Context::default(), &toolbar, 24.0)
+    });
+    let ctx = egui::Context::default();
+    bench("100 icons, cache hit", || frame(&ctx, &toolbar, 24.0));
+}
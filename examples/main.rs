@@ -20,6 +20,7 @@ fn main() {
         Box::new(|_cc| {
             Box::new(Test(
                 8,
+                0,
                 RetainedImage::from_color_image(
                     "",
                     load_image_bytes(include_bytes!("uv.png")).unwrap(),
@@ -30,7 +31,7 @@ fn main() {
     .unwrap()
 }
 
-struct Test(usize, RetainedImage);
+struct Test(usize, usize, RetainedImage);
 impl eframe::App for Test {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         #[cfg(feature = "puffin")]
@@ -63,9 +64,23 @@ impl eframe::App for Test {
                             .show(ui);
                     });
                     ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("ui color");
+                        Svg::new(ICON)
+                            .with_ui_color(|visuals| visuals.hyperlink_color)
+                            .show(ui);
+                    });
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("color map");
+                        Svg::new(ICON)
+                            .with_color_map(|c| Color32::from_rgb(c.g(), c.b(), c.r()))
+                            .show(ui);
+                    });
+                    ui.separator();
                     ui.vertical(|ui| {
                         ui.label("uv");
-                        Svg::new(ICON).with_texture(self.1.texture_id(ctx)).show(ui);
+                        Svg::new(ICON).with_texture(self.2.texture_id(ctx)).show(ui);
                     });
                 });
                 ui.separator();
@@ -115,6 +130,14 @@ impl eframe::App for Test {
                             .show_sized(ui, size);
                     });
                     ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("scale down");
+                        rect!(ui, size);
+                        Svg::new(ICON)
+                            .with_fit_mode(FitMode::ScaleDown(Margin::default()))
+                            .show_sized(ui, size);
+                    });
+                    ui.separator();
                     ui.vertical(|ui| {
                         ui.label("contain margin");
                         let margin = Margin {
@@ -142,6 +165,15 @@ impl eframe::App for Test {
                             .show_sized(ui, size);
                     });
                     ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("left top");
+                        rect!(ui, size);
+                        Svg::new(ICON)
+                            .with_fit_mode(FitMode::Factor(0.5))
+                            .with_align(Align2::LEFT_TOP)
+                            .show_sized(ui, size);
+                    });
+                    ui.separator();
                     ui.vertical(|ui| {
                         ui.label("cover");
                         rect!(ui, size);
@@ -149,8 +181,34 @@ impl eframe::App for Test {
                             .with_fit_mode(FitMode::Cover)
                             .show_sized(ui, size);
                     });
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("stretch");
+                        rect!(ui, size);
+                        Svg::new(ICON)
+                            .with_fit_mode(FitMode::Stretch)
+                            .show_sized(ui, size);
+                    });
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        ui.label("cover right");
+                        rect!(ui, size);
+                        Svg::new(ICON)
+                            .with_fit_mode(FitMode::Cover)
+                            .with_align(Align2::RIGHT_CENTER)
+                            .show_sized(ui, size);
+                    });
                 });
                 ui.separator();
+                ui.label("tile");
+                let width = ui.available_width();
+                Svg::new(ICON)
+                    .with_fit_mode(FitMode::Tile {
+                        tile_size: Vec2::new(16.0, 8.0),
+                        spacing: Vec2::splat(4.0),
+                    })
+                    .show_sized(ui, [width, 48.0]);
+                ui.separator();
                 ui.horizontal(|ui| {
                     ui.set_height(64.0);
                     ui.label("inline");
@@ -190,6 +248,41 @@ impl eframe::App for Test {
                     }
                 });
                 ui.separator();
+                ui.horizontal(|ui| {
+                    if Svg::new(ICON)
+                        .with_color_from_style()
+                        .with_background_from_style()
+                        .with_sense(Sense::click())
+                        .with_id_source("click counter")
+                        .show(ui)
+                        .clicked()
+                    {
+                        self.1 += 1;
+                    }
+                    ui.label(format!("clicked {} times", self.1));
+                    Svg::new(ICON)
+                        .with_state_colors(
+                            Color32::GRAY,
+                            Color32::LIGHT_BLUE,
+                            Color32::BLUE,
+                            Color32::LIGHT_GRAY,
+                        )
+                        .with_sense(Sense::click())
+                        .show(ui);
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("enabled");
+                    Svg::new(include_bytes!("test_gradient.svg")).show(ui);
+                    ui.add_enabled_ui(false, |ui| {
+                        ui.label("disabled");
+                        Svg::new(include_bytes!("test_gradient.svg")).show(ui);
+                        Svg::new(include_bytes!("test_gradient.svg"))
+                            .with_disabled_behavior(DisabledBehavior::Grayscale)
+                            .show(ui);
+                    });
+                });
+                ui.separator();
                 ui.label("button like");
                 ui.horizontal(|ui| {
                     Svg::new(ICON)
@@ -244,13 +337,123 @@ impl eframe::App for Test {
                         .show_justified(ui);
                 });
                 ui.separator();
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    ui.label("rotation");
+                    for i in 0..4 {
+                        Svg::new(ICON)
+                            .with_rotation(i as f32 * std::f32::consts::FRAC_PI_2)
+                            .show_sized(ui, [64.0, 64.0]);
+                    }
+                    Svg::new(ICON)
+                        .with_rotation(ui.input(|i| i.time) as f32)
+                        .show_sized(ui, [64.0, 64.0]);
+                    ui.ctx().request_repaint();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    ui.label("flip");
+                    for (horizontal, vertical) in
+                        [(false, false), (true, false), (false, true), (true, true)]
+                    {
+                        Svg::new(ICON)
+                            .with_flip(horizontal, vertical)
+                            .show_sized(ui, [64.0, 64.0]);
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    ui.label("color / tint");
+                    let shadow = Svg::new(include_bytes!("test_shadow.svg"));
+                    shadow.clone().show_sized(ui, [64.0, 64.0]);
+                    shadow
+                        .clone()
+                        .with_color(Color32::LIGHT_RED)
+                        .show_sized(ui, [64.0, 64.0]);
+                    shadow
+                        .with_tint(Color32::LIGHT_RED)
+                        .show_sized(ui, [64.0, 64.0]);
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    ui.label("pattern");
+                    let pattern = Svg::new(include_bytes!("test_pattern.svg"));
+                    #[cfg(feature = "raster")]
+                    pattern
+                        .clone()
+                        .with_backend(Backend::Raster)
+                        .show_sized(ui, [64.0, 64.0]);
+                    pattern.show_sized(ui, [64.0, 64.0]);
+                });
+                ui.separator();
+                ui.label("translucent overlap");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_overlap.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("fill rule");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_fill_rule.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("dash");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_dash.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("visibility");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_visibility.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("miter limit");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_miter.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("clip path");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_clip.svg")).show_justified(ui);
+                });
+                ui.separator();
+                ui.label("mask");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_mask.svg")).show_justified(ui);
+                });
+                ui.separator();
+                #[cfg(feature = "gradient")]
+                {
+                    ui.label("gradients");
+                    ui.horizontal(|ui| {
+                        ui.set_height(64.0);
+                        Svg::new(include_bytes!("test_gradient_units.svg")).show_justified(ui);
+                    });
+                    ui.separator();
+                }
+                ui.label("preserve aspect ratio");
+                ui.horizontal(|ui| {
+                    ui.set_height(64.0);
+                    Svg::new(include_bytes!("test_align.svg")).show_justified(ui);
+                });
+                ui.separator();
                 ui.horizontal(|ui| {
                     ui.label("many");
                     ui.add(DragValue::new(&mut self.0));
                 });
                 ui.horizontal_wrapped(|ui| {
+                    let icon = Svg::new(ICON);
                     for _ in 0..self.0 {
-                        Svg::new(ICON).show(ui);
+                        icon.clone().show(ui);
                     }
                 });
             });
@@ -0,0 +1,76 @@
+use egui::epaint::ahash::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// a parsed tree and the placeholder color of currentColor in it
+type Parsed = (Rc<usvg::Tree>, Option<egui::Color32>);
+
+thread_local! {
+    static TREES: RefCell<TreeCache> = Default::default();
+}
+
+/// parsed svg trees keyed on their source, evicting the least recently used one
+/// when `capacity` is exceeded
+#[derive(Default)]
+struct TreeCache {
+    trees: HashMap<u64, (Parsed, u64)>,
+    tick: u64,
+    capacity: Option<usize>,
+}
+impl TreeCache {
+    fn evict(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while self.trees.len() > capacity {
+            let Some(key) = self
+                .trees
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| *key)
+            else {
+                return;
+            };
+            self.trees.remove(&key);
+        }
+    }
+}
+
+/// get the cached tree for `key` or insert the one created by `f` if it succeeds
+pub fn get_or_try_insert<E>(
+    key: u64,
+    f: impl FnOnce() -> Result<(usvg::Tree, Option<egui::Color32>), E>,
+) -> Result<Parsed, E> {
+    TREES.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.tick += 1;
+        let tick = cache.tick;
+        if let Some((tree, last_used)) = cache.trees.get_mut(&key) {
+            *last_used = tick;
+            return Ok(tree.clone());
+        }
+
+        let (tree, current_color) = f()?;
+        let tree = (Rc::new(tree), current_color);
+        cache.trees.insert(key, (tree.clone(), tick));
+        cache.evict();
+        Ok(tree)
+    })
+}
+#[cfg(feature = "hot_reload")]
+pub fn remove(key: u64) {
+    TREES.with(|cache| cache.borrow_mut().trees.remove(&key));
+}
+pub fn clear() {
+    TREES.with(|cache| cache.borrow_mut().trees.clear());
+}
+pub fn len() -> usize {
+    TREES.with(|cache| cache.borrow().trees.len())
+}
+pub fn set_capacity(capacity: Option<usize>) {
+    TREES.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.capacity = capacity;
+        cache.evict();
+    });
+}
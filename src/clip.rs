@@ -0,0 +1,206 @@
+use crate::tessellation::PathConvIter;
+use crate::*;
+use lyon::lyon_tessellation::VertexBuffers;
+use lyon::path::iterator::PathIterator;
+use lyon::path::PathEvent;
+
+/// the clip region as a convex polygon in svg space.
+/// non-convex or multi-shape clips fall back to their bounding box
+pub fn clip_polygon(
+    clip_path: &usvg::ClipPath,
+    transform: usvg::Transform,
+    tolerance: f32,
+) -> Option<Vec<Pos2>> {
+    // relative to the clipped element's bounding box, not supported
+    if clip_path.units == usvg::Units::ObjectBoundingBox {
+        return None;
+    }
+
+    let mut polygons = Vec::new();
+    collect_polygons(
+        &clip_path.root,
+        append_transform(transform, clip_path.transform),
+        tolerance,
+        &mut polygons,
+    );
+
+    match polygons.as_slice() {
+        [polygon] if is_convex(polygon) => Some(polygon.clone()),
+        _ => {
+            let points = polygons.iter().flatten().copied().collect::<Vec<_>>();
+            if points.is_empty() {
+                return Some(Vec::new());
+            }
+            let rect = Rect::from_points(&points);
+            Some(vec![
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ])
+        }
+    }
+}
+fn collect_polygons(
+    parent: &usvg::Node,
+    transform: usvg::Transform,
+    tolerance: f32,
+    polygons: &mut Vec<Vec<Pos2>>,
+) {
+    for node in parent.children() {
+        match &*node.borrow() {
+            usvg::NodeKind::Path(p) if p.visibility != usvg::Visibility::Visible => {}
+            usvg::NodeKind::Path(p) => {
+                let transform = append_transform(transform, p.transform);
+                let mut polygon = Vec::new();
+                for event in PathConvIter::new(p).flattened(tolerance) {
+                    let point = match event {
+                        PathEvent::Begin { at } => {
+                            if polygon.len() >= 3 {
+                                polygons.push(std::mem::take(&mut polygon));
+                            }
+                            polygon.clear();
+                            at
+                        }
+                        PathEvent::Line { to, .. } => to,
+                        _ => continue,
+                    };
+                    let (x, y) = transform.apply(point.x as _, point.y as _);
+                    let point = Pos2::new(x as _, y as _);
+                    if polygon.first() != Some(&point) {
+                        polygon.push(point);
+                    }
+                }
+                if polygon.len() >= 3 {
+                    polygons.push(polygon);
+                }
+            }
+            usvg::NodeKind::Group(g) => collect_polygons(
+                &node,
+                append_transform(transform, g.transform),
+                tolerance,
+                polygons,
+            ),
+            usvg::NodeKind::Image(_) | usvg::NodeKind::Text(_) => {}
+        }
+    }
+}
+fn is_convex(polygon: &[Pos2]) -> bool {
+    let mut sign = 0.0;
+    for i in 0..polygon.len() {
+        let [a, b, c] = [0, 1, 2].map(|o| polygon[(i + o) % polygon.len()]);
+        let cross = (b - a).x * (c - b).y - (b - a).y * (c - b).x;
+        if cross.abs() <= f32::EPSILON {
+            continue;
+        }
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+    true
+}
+fn signed_area(polygon: &[Pos2]) -> f32 {
+    (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// clip every triangle from `first_index` on against a convex polygon in egui space.
+/// `breaks` are sorted index positions that are moved to stay between the same triangles
+pub fn clip_triangles(
+    buffer: &mut VertexBuffers<epaint::Vertex, u32>,
+    first_index: usize,
+    polygon: &[Pos2],
+    breaks: &mut [usize],
+) {
+    let triangles = buffer.indices.split_off(first_index);
+    let mut breaks = breaks.iter_mut().filter(|b| **b >= first_index).peekable();
+    if polygon.len() < 3 {
+        breaks.for_each(|b| *b = first_index);
+        return;
+    }
+    let orientation = signed_area(polygon).signum();
+    let edges = (0..polygon.len())
+        .map(|i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+        .collect::<Vec<_>>();
+    let inside = |(a, b): (Pos2, Pos2), p: Pos2| {
+        ((b - a).x * (p - a).y - (b - a).y * (p - a).x) * orientation >= 0.0
+    };
+
+    for (i, triangle) in triangles.chunks_exact(3).enumerate() {
+        while let Some(b) = breaks.next_if(|b| **b <= first_index + 3 * i) {
+            *b = buffer.indices.len();
+        }
+        let mut vertices = triangle
+            .iter()
+            .map(|i| buffer.vertices[*i as usize])
+            .collect::<Vec<_>>();
+
+        if vertices
+            .iter()
+            .all(|v| edges.iter().all(|e| inside(*e, v.pos)))
+        {
+            buffer.indices.extend_from_slice(triangle);
+            continue;
+        }
+
+        // sutherland-hodgman
+        for edge in &edges {
+            let input = std::mem::take(&mut vertices);
+            for (i, current) in input.iter().enumerate() {
+                let previous = input[(i + input.len() - 1) % input.len()];
+                let (current_inside, previous_inside) =
+                    (inside(*edge, current.pos), inside(*edge, previous.pos));
+                if current_inside != previous_inside {
+                    vertices.push(intersect(*edge, previous, *current));
+                }
+                if current_inside {
+                    vertices.push(*current);
+                }
+            }
+            if vertices.is_empty() {
+                break;
+            }
+        }
+        if vertices.len() < 3 {
+            continue;
+        }
+
+        let base = buffer.vertices.len() as u32;
+        for i in 1..vertices.len() as u32 - 1 {
+            buffer
+                .indices
+                .extend_from_slice(&[base, base + i, base + i + 1]);
+        }
+        buffer.vertices.extend(vertices);
+    }
+    breaks.for_each(|b| *b = buffer.indices.len());
+}
+fn intersect((a, b): (Pos2, Pos2), from: epaint::Vertex, to: epaint::Vertex) -> epaint::Vertex {
+    let edge = b - a;
+    let segment = to.pos - from.pos;
+    let denominator = edge.x * segment.y - edge.y * segment.x;
+    let t = if denominator.abs() <= f32::EPSILON {
+        0.0
+    } else {
+        ((a - from.pos).x * edge.y - (a - from.pos).y * edge.x) / -denominator
+    };
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    epaint::Vertex {
+        pos: from.pos + segment * t,
+        uv: from.uv + (to.uv - from.uv) * t,
+        color: Color32::from_rgba_premultiplied(
+            mix(from.color.r(), to.color.r()),
+            mix(from.color.g(), to.color.g()),
+            mix(from.color.b(), to.color.b()),
+            mix(from.color.a(), to.color.a()),
+        ),
+    }
+}
@@ -1,55 +1,124 @@
 use crate::*;
-use lyon::geom::euclid::Vector2D;
-use lyon::geom::Line;
-use lyon::math::Point;
 
+#[derive(Clone)]
 pub struct GradientColor {
     pub fac: f32,
     pub color: Color32,
 }
 
+/// `transform` maps svg space back into the gradient's own space
+#[derive(Clone)]
+pub enum GradientKind {
+    Linear {
+        start: Pos2,
+        end: Pos2,
+        transform: usvg::Transform,
+    },
+    Radial {
+        center: Pos2,
+        focal: Pos2,
+        radius: f32,
+        transform: usvg::Transform,
+    },
+}
+
+#[derive(Clone)]
 pub struct Gradient {
     pub colors: Vec<GradientColor>,
-    pub start: Pos2,
-    pub end: Pos2,
+    pub kind: GradientKind,
     pub wrap_mode: TextureWrapMode,
 }
 impl Gradient {
-    pub fn new(g: &usvg::LinearGradient, transform: usvg::Transform) -> Self {
+    pub fn new(
+        g: &usvg::LinearGradient,
+        transform: usvg::Transform,
+        opacity: f64,
+        color_func: Option<&dyn Fn(Color32) -> Color32>,
+    ) -> Self {
+        let gradient_transform = append_transform(transform, g.transform);
+        Gradient {
+            colors: colors_from_stops(&g.stops, opacity, color_func),
+            kind: GradientKind::Linear {
+                start: Pos2::new(g.x1 as _, g.y1 as _),
+                end: Pos2::new(g.x2 as _, g.y2 as _),
+                transform: invert_transform(gradient_transform),
+            },
+            wrap_mode: to_wrap_mode(g.spread_method),
+        }
+    }
+    pub fn new_radial(
+        g: &usvg::RadialGradient,
+        transform: usvg::Transform,
+        opacity: f64,
+        color_func: Option<&dyn Fn(Color32) -> Color32>,
+    ) -> Self {
         let gradient_transform = append_transform(transform, g.transform);
-        let ((x1, y1), (x2, y2)) = (
-            gradient_transform.apply(g.x1, g.y1),
-            gradient_transform.apply(g.x2, g.y2),
-        );
         Gradient {
-            colors: g
-                .stops
-                .iter()
-                .map(|f| GradientColor {
-                    fac: f.offset.get() as _,
-                    color: to_egui_color(f.color, f.opacity.get()),
-                })
-                .collect(),
-            start: Pos2::new(x1 as _, y1 as _),
-            end: Pos2::new(x2 as _, y2 as _),
-            wrap_mode: match g.spread_method {
-                usvg::SpreadMethod::Pad => TextureWrapMode::Clamp,
-                usvg::SpreadMethod::Reflect => TextureWrapMode::Mirror,
-                usvg::SpreadMethod::Repeat => TextureWrapMode::Repeat,
+            colors: colors_from_stops(&g.stops, opacity, color_func),
+            kind: GradientKind::Radial {
+                center: Pos2::new(g.cx as _, g.cy as _),
+                focal: Pos2::new(g.fx as _, g.fy as _),
+                radius: g.r.get() as _,
+                transform: invert_transform(gradient_transform),
             },
+            wrap_mode: to_wrap_mode(g.spread_method),
         }
     }
     pub fn color_at_pos(&self, pos: Pos2) -> Color32 {
-        let fac = {
-            let line = Line {
-                point: Point::new(self.start.x, self.start.y),
-                vector: Vector2D::new(-(self.end.x - self.start.x), self.end.y - self.start.y).yx(),
-            };
-            line.signed_distance_to_point(&Point::new(pos.x, pos.y)) / line.vector.length()
+        let fac = match self.kind {
+            GradientKind::Linear {
+                start,
+                end,
+                transform,
+            } => {
+                // project in the gradient's own space so skewed gradients stay perpendicular there
+                let pos = {
+                    let (x, y) = transform.apply(pos.x as _, pos.y as _);
+                    Pos2::new(x as _, y as _)
+                };
+                let d = end - start;
+                // a zero length gradient is painted with the last stop
+                if d.length_sq() < f32::EPSILON {
+                    1.0
+                } else {
+                    (pos - start).dot(d) / d.length_sq()
+                }
+            }
+            GradientKind::Radial {
+                center,
+                focal,
+                radius,
+                transform,
+            } => {
+                let pos = {
+                    let (x, y) = transform.apply(pos.x as _, pos.y as _);
+                    Pos2::new(x as _, y as _)
+                };
+                // find the largest `t` so that `pos` lies on the circle
+                // centered at `focal + t * (center - focal)` with radius `t * radius`
+                let d = center - focal;
+                let q = pos - focal;
+                let a = d.dot(d) - radius * radius;
+                let b = -2.0 * q.dot(d);
+                let c = q.dot(q);
+                if a.abs() < f32::EPSILON {
+                    if b.abs() < f32::EPSILON {
+                        0.0
+                    } else {
+                        -c / b
+                    }
+                } else {
+                    let discriminant = (b * b - 4.0 * a * c).max(0.0).sqrt();
+                    f32::max(
+                        (-b + discriminant) / (2.0 * a),
+                        (-b - discriminant) / (2.0 * a),
+                    )
+                }
+            }
         };
 
         let fac = match self.wrap_mode {
-            TextureWrapMode::Clamp => fac,
+            TextureWrapMode::Clamp => fac.clamp(0.0, 1.0),
             TextureWrapMode::Mirror => 1.0 - (fac.abs() % 2.0 - 1.0).abs(),
             TextureWrapMode::Repeat => fac - fac.floor(),
         };
@@ -61,7 +130,7 @@ impl Gradient {
             color_a = color[0].color;
             color_b = color[1].color;
             if fac < color[1].fac {
-                local_fac = (fac - color[0].fac) / (color[1].fac - color[0].fac);
+                local_fac = ((fac - color[0].fac) / (color[1].fac - color[0].fac)).clamp(0.0, 1.0);
                 break;
             }
         }
@@ -80,3 +149,24 @@ impl Gradient {
         )
     }
 }
+
+fn colors_from_stops(
+    stops: &[usvg::Stop],
+    opacity: f64,
+    color_func: Option<&dyn Fn(Color32) -> Color32>,
+) -> Vec<GradientColor> {
+    stops
+        .iter()
+        .map(|f| GradientColor {
+            fac: f.offset.get() as _,
+            color: to_mapped_egui_color(f.color, f.opacity.get() * opacity, color_func),
+        })
+        .collect()
+}
+fn to_wrap_mode(spread_method: usvg::SpreadMethod) -> TextureWrapMode {
+    match spread_method {
+        usvg::SpreadMethod::Pad => TextureWrapMode::Clamp,
+        usvg::SpreadMethod::Reflect => TextureWrapMode::Mirror,
+        usvg::SpreadMethod::Repeat => TextureWrapMode::Repeat,
+    }
+}
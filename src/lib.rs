@@ -1,13 +1,20 @@
 use egui::*;
 use utils::*;
 
+#[cfg(feature = "cached")]
+mod cache;
+mod clip;
 #[cfg(feature = "gradient")]
 mod gradient;
+mod mask;
+#[cfg(feature = "image")]
+mod raster;
+#[cfg(feature = "raster")]
+mod rasterize;
 mod tessellation;
 mod utils;
 
 /// ???
-#[cfg(feature = "cached")]
 macro_rules! bytes {
     ($t:expr, $T:ty) => {
         unsafe { std::mem::transmute::<$T, [u8; std::mem::size_of::<$T>()]>($t) }
@@ -21,6 +28,15 @@ pub enum FitMode {
     Factor(f32),
     Cover,
     Contain(Margin),
+    /// fill the frame, ignoring the aspect ratio
+    Stretch,
+    /// like [`FitMode::Contain`], but never larger than the svg's original size
+    ScaleDown(Margin),
+    /// repeat the shape at `tile_size` to cover the frame, starting at its top left corner
+    Tile {
+        tile_size: Vec2,
+        spacing: Vec2,
+    },
 }
 
 #[derive(Clone, Copy)]
@@ -30,15 +46,118 @@ pub enum TextureWrapMode {
     Mirror,
 }
 
+/// whether a color is used to fill or to stroke a path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaintKind {
+    Fill,
+    Stroke,
+}
+
+/// an element's color to map, see [`Svg::with_color_map_ex`]
+#[derive(Clone, Copy, Debug)]
+pub struct ColorContext<'a> {
+    /// the original svg color, always opaque
+    pub color: Color32,
+    pub kind: PaintKind,
+    /// id of the path, empty if it has none
+    pub id: &'a str,
+    /// fill or stroke opacity including group opacity, applied after mapping
+    pub opacity: f32,
+}
+
+type ColorFunc = std::sync::Arc<dyn Fn(&ColorContext) -> Color32 + Send + Sync>;
+
+/// how the icon is drawn
+#[cfg(feature = "raster")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// tessellate the shapes into a mesh, which stays sharp when transformed
+    Tessellate,
+    /// render the svg with resvg into a texture of the icon's size in pixels.
+    /// supports everything resvg does, like filters and patterns
+    Raster,
+}
+
+/// which parts of the shapes are drawn, see [`Svg::with_paint_mode`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaintMode {
+    FillAndStroke,
+    FillOnly,
+    StrokeOnly,
+    /// only draw outlines of the given width in points. strokes keep their paint,
+    /// filled shapes without a stroke are outlined with their fill paint
+    OutlineAll(f32),
+}
+
+/// cost of a tessellated icon, see [`Svg::stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TessellationStats {
+    pub vertices: usize,
+    pub indices: usize,
+    /// number of paths in the mesh
+    pub paths: usize,
+    /// whether paths were dropped because of [`Svg::with_vertex_budget`]
+    /// or [`Svg::with_path_budget`]
+    pub truncated: bool,
+}
+
+/// tessellation quality, see [`Svg::with_quality`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quality {
+    /// coarse curves, e.g. for icons that are animated or shown very briefly
+    Draft,
+    /// the default tolerance of `1.0`
+    Normal,
+    /// smooth curves for large or zoomed in icons
+    High,
+}
+impl Quality {
+    /// the tessellation tolerance of this quality
+    pub fn tolerance(self) -> f32 {
+        match self {
+            Quality::Draft => 4.0,
+            Quality::Normal => 1.0,
+            Quality::High => 0.25,
+        }
+    }
+}
+
+/// how the icon looks when the ui is disabled
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DisabledBehavior {
+    /// fade the colors towards the weak text color
+    Fade,
+    /// remove the colors' saturation
+    Grayscale,
+    /// draw the icon as usual
+    None,
+}
+
+#[derive(Clone)]
 enum ColorOverride {
     None,
     FromStyle,
     Color(Color32),
+    FromVisuals(std::sync::Arc<dyn Fn(&Visuals) -> Color32 + Send + Sync>),
+    Tint(Color32),
     Texture(TextureId),
     #[cfg(feature = "gradient")]
     Gradient(gradient::Gradient),
+    State {
+        normal: Color32,
+        hovered: Color32,
+        active: Color32,
+        disabled: Color32,
+    },
+    Animated {
+        id: Id,
+        from: Color32,
+        to: Color32,
+        active: bool,
+    },
 }
 
+#[derive(Clone)]
 enum Background {
     None,
     FromStyle,
@@ -49,35 +168,333 @@ enum Background {
     },
 }
 
-#[cfg(not(feature = "cached"))]
-type SvgTree = usvg::Tree;
-#[cfg(feature = "cached")]
-type SvgTree = (u64, std::rc::Rc<usvg::Tree>);
+/// error returned when loading a svg fails
+#[derive(Debug)]
+pub enum SvgError {
+    /// the data could not be parsed as svg
+    Parse(usvg::Error),
+    /// the svg file could not be read
+    Io(std::io::Error),
+    /// the `data:` uri could not be decoded
+    DataUri,
+}
+impl std::fmt::Display for SvgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgError::Parse(e) => write!(f, "failed to parse svg: {e}"),
+            SvgError::Io(e) => write!(f, "failed to read svg: {e}"),
+            SvgError::DataUri => write!(f, "failed to decode svg data uri"),
+        }
+    }
+}
+impl std::error::Error for SvgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SvgError::Parse(e) => Some(e),
+            SvgError::Io(e) => Some(e),
+            SvgError::DataUri => None,
+        }
+    }
+}
+impl SvgError {
+    /// the underlying usvg parse error, if any
+    pub fn as_usvg(&self) -> Option<&usvg::Error> {
+        match self {
+            SvgError::Parse(e) => Some(e),
+            SvgError::Io(_) | SvgError::DataUri => None,
+        }
+    }
+}
+impl From<usvg::Error> for SvgError {
+    fn from(e: usvg::Error) -> Self {
+        SvgError::Parse(e)
+    }
+}
+impl From<std::io::Error> for SvgError {
+    fn from(e: std::io::Error) -> Self {
+        SvgError::Io(e)
+    }
+}
+
+impl std::str::FromStr for Svg {
+    type Err = SvgError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
+    }
+}
+
+/// parse a svg, returning the tree and the color currentColor was parsed as, if used.
+/// usvg resolves currentColor while parsing, so where no `color` property is in effect
+/// it's parsed as a placeholder color that is replaced when tessellating,
+/// see [`Svg::with_current_color`]
+fn parse_tree(
+    data: &[u8],
+    options: &usvg::Options,
+) -> Result<(usvg::Tree, Option<Color32>), SvgError> {
+    let data = if data.starts_with(b"data:") {
+        let uri = std::str::from_utf8(data).map_err(|_| usvg::Error::NotAnUtf8Str)?;
+        let (data, _) = data_url::DataUrl::process(uri.trim())
+            .ok()
+            .and_then(|uri| uri.decode_to_vec().ok())
+            .ok_or(SvgError::DataUri)?;
+        std::borrow::Cow::Owned(data)
+    } else {
+        std::borrow::Cow::Borrowed(data)
+    };
+    let data = if data.starts_with(&[0x1f, 0x8b]) {
+        std::borrow::Cow::Owned(usvg::decompress_svgz(&data)?)
+    } else {
+        data
+    };
+    let text = std::str::from_utf8(&data).map_err(|_| usvg::Error::NotAnUtf8Str)?;
+    let doc = usvg::roxmltree::Document::parse_with_options(
+        text,
+        usvg::roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .map_err(usvg::Error::from)?;
+    let ranges = current_color_ranges(&doc);
+    #[cfg_attr(not(feature = "text"), allow(unused_mut))]
+    let (mut tree, current_color) = if ranges.is_empty() {
+        (usvg::Tree::from_xmltree(&doc, options)?, None)
+    } else {
+        let placeholder = unused_color(text);
+        let [r, g, b, _] = placeholder.to_array();
+        let hex = format!("#{r:02x}{g:02x}{b:02x}");
+        let mut replaced = String::with_capacity(text.len());
+        let mut last = 0;
+        for range in ranges {
+            replaced.push_str(&text[last..range.start]);
+            replaced.push_str(&text[range.clone()].replace("currentColor", &hex));
+            last = range.end;
+        }
+        replaced.push_str(&text[last..]);
+        (usvg::Tree::from_str(&replaced, options)?, Some(placeholder))
+    };
+
+    #[cfg(feature = "text")]
+    {
+        use usvg_text_layout::*;
+
+        thread_local! {
+            static FONT_DB: fontdb::Database = {
+                let mut db = fontdb::Database::new();
+                db.load_system_fonts();
+                db
+            };
+        }
+        FONT_DB.with(|db| tree.convert_text(db));
+    }
+
+    Ok((tree, current_color))
+}
+
+/// what identifies a svg tree in the tree and mesh caches
+#[derive(Clone, Copy)]
+enum TreeKey {
+    /// the content, or its address and length with `static_cached`
+    Data,
+    /// always the content, even with `static_cached`
+    Content,
+    /// chosen by the user, see [`Svg::new_with_key`]
+    Custom(u64),
+}
+
+fn load_tree(data: &[u8], options: &usvg::Options, tree_key: TreeKey) -> Result<SvgTree, SvgError> {
+    #[cfg(feature = "puffin")]
+    puffin::profile_function!();
+
+    let tree = {
+        use egui::epaint::ahash::RandomState;
+        use std::hash::*;
+
+        let key = {
+            let mut hasher = RandomState::with_seed(0).build_hasher();
+
+            match tree_key {
+                TreeKey::Custom(key) => key.hash(&mut hasher),
+                TreeKey::Data if cfg!(feature = "static_cached") => {
+                    (data.as_ptr(), data.len()).hash(&mut hasher)
+                }
+                TreeKey::Data | TreeKey::Content => data.hash(&mut hasher),
+            }
 
+            options.resources_dir.hash(&mut hasher);
+            options.dpi.to_bits().hash(&mut hasher);
+            options.font_family.hash(&mut hasher);
+            options.font_size.to_bits().hash(&mut hasher);
+            options.languages.hash(&mut hasher);
+            options.default_size.width().to_bits().hash(&mut hasher);
+            options.default_size.height().to_bits().hash(&mut hasher);
+
+            hasher.finish()
+        };
+
+        #[cfg(not(feature = "cached"))]
+        let (tree, current_color) = {
+            let (tree, current_color) = parse_tree(data, options)?;
+            (std::rc::Rc::new(tree), current_color)
+        };
+        #[cfg(feature = "cached")]
+        let (tree, current_color) = cache::get_or_try_insert(key, || parse_tree(data, options))?;
+
+        (key, tree, current_color)
+    };
+
+    Ok(tree)
+}
+
+/// `available` length, or `empty` if there is none and `unbounded` if it's infinite
+fn justified_length(available: f32, empty: f32, unbounded: f32) -> f32 {
+    if !available.is_finite() {
+        unbounded
+    } else if available <= 0.0 {
+        empty
+    } else {
+        available
+    }
+}
+
+/// distance from the top of a row of text to its baseline. egui doesn't expose the font's
+/// ascent, so it's where the bottom of a capital letter ends up
+fn text_baseline(fonts: &egui::epaint::Fonts, font_id: egui::FontId) -> f32 {
+    let galley = fonts.layout_no_wrap("H".to_owned(), font_id, Color32::WHITE);
+    galley
+        .rows
+        .first()
+        .and_then(|row| row.glyphs.first())
+        .map_or(galley.size().y, |glyph| {
+            glyph.pos.y + glyph.uv_rect.offset.y + glyph.uv_rect.size.y
+        })
+}
+
+/// the parsed tree with a key identifying it in the mesh cache,
+/// and the placeholder color of currentColor in the tree
+type SvgTree = (u64, std::rc::Rc<usvg::Tree>, Option<Color32>);
+
+/// an svg icon. cloning is cheap as the parsed svg tree is shared
+#[derive(Clone)]
 pub struct Svg {
     tree: SvgTree,
     color_override: ColorOverride,
     background: Background,
     tolerance: f32,
     scale_tolerance: bool,
+    pixel_tolerance: Option<f32>,
     fit_mode: FitMode,
     sense: Sense,
+    id_source: Option<Id>,
+    disabled_behavior: DisabledBehavior,
+    color_func: Option<ColorFunc>,
+    color_key: Option<u64>,
+    id_colors: Vec<(String, Color32)>,
+    align: Align2,
+    rotation: f32,
+    rtl_mirror: bool,
+    flip: (bool, bool),
+    opacity: f32,
+    desaturate: f32,
+    inverted: bool,
+    paint_fallback: Option<Color32>,
+    current_color: Option<Color32>,
+    #[cfg(feature = "raster")]
+    backend: Backend,
+    #[cfg(feature = "raster")]
+    supersample: u32,
+    #[cfg(feature = "gradient")]
+    gradient_subdivision: f32,
+    size_rounding: f32,
+    max_vertices: Option<usize>,
+    paint_mode: PaintMode,
+    stroke_override: Option<(Color32, f32)>,
+    vertex_budget: usize,
+    path_budget: usize,
+    viewport: Option<Rect>,
+    trim: bool,
+    /// content bounds used instead of the viewport, see [`Svg::with_trim`]
+    trim_bounds: Option<Rect>,
+    element: Option<String>,
+    text_style: Option<TextStyle>,
+    #[cfg(feature = "hot_reload")]
+    hot_reload: Option<std::rc::Rc<HotReload>>,
+}
+/// source file of a hot reloaded svg, shared between clones
+#[cfg(feature = "hot_reload")]
+struct HotReload {
+    path: std::path::PathBuf,
+    modified: std::cell::Cell<Option<std::time::SystemTime>>,
+    tree: std::cell::RefCell<SvgTree>,
 }
-#[cfg(feature = "cached")]
 impl std::hash::Hash for Svg {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         let Self {
-            tree: (key, _),
+            tree: (key, _, _),
             color_override: _,
             background: _,
             tolerance,
             scale_tolerance,
+            pixel_tolerance: _,
             fit_mode,
             sense: _,
+            id_source: _,
+            disabled_behavior: _,
+            color_func: _,
+            color_key,
+            id_colors,
+            align: _,
+            rotation: _,
+            rtl_mirror: _,
+            flip: _,
+            opacity: _,
+            desaturate: _,
+            inverted: _,
+            paint_fallback,
+            current_color: _,
+            #[cfg(feature = "raster")]
+                backend: _,
+            #[cfg(feature = "raster")]
+                supersample: _,
+            #[cfg(feature = "gradient")]
+            gradient_subdivision,
+            size_rounding: _,
+            max_vertices: _,
+            paint_mode,
+            stroke_override,
+            vertex_budget,
+            path_budget,
+            viewport,
+            trim: _,
+            trim_bounds,
+            element,
+            text_style: _,
+            #[cfg(feature = "hot_reload")]
+                hot_reload: _,
         } = self;
         key.hash(state);
-        bytes!(*tolerance, f32).hash(state);
+        tolerance.to_bits().hash(state);
         scale_tolerance.hash(state);
+        viewport.map(|r| bytes!(r, Rect)).hash(state);
+        trim_bounds.map(|r| bytes!(r, Rect)).hash(state);
+        element.hash(state);
+        paint_fallback.hash(state);
+        color_key.hash(state);
+        id_colors.hash(state);
+        match paint_mode {
+            PaintMode::FillAndStroke => 0usize.hash(state),
+            PaintMode::FillOnly => 1usize.hash(state),
+            PaintMode::StrokeOnly => 2usize.hash(state),
+            PaintMode::OutlineAll(width) => {
+                3usize.hash(state);
+                width.to_bits().hash(state);
+            }
+        }
+        stroke_override.map(|(c, w)| (c, w.to_bits())).hash(state);
+        vertex_budget.hash(state);
+        path_budget.hash(state);
+        #[cfg(feature = "gradient")]
+        gradient_subdivision.to_bits().hash(state);
         match fit_mode {
             FitMode::None => 0usize.hash(state),
             FitMode::Size(s) => {
@@ -86,96 +503,384 @@ impl std::hash::Hash for Svg {
             }
             FitMode::Factor(f) => {
                 2usize.hash(state);
-                bytes!(*f, f32).hash(state);
+                f.to_bits().hash(state);
             }
             FitMode::Cover => 3usize.hash(state),
             FitMode::Contain(margin) => {
                 4usize.hash(state);
                 bytes!(*margin, Margin).hash(state);
             }
+            FitMode::Stretch => 5usize.hash(state),
+            FitMode::ScaleDown(margin) => {
+                6usize.hash(state);
+                bytes!(*margin, Margin).hash(state);
+            }
+            FitMode::Tile { tile_size, spacing } => {
+                7usize.hash(state);
+                bytes!(*tile_size, Vec2).hash(state);
+                bytes!(*spacing, Vec2).hash(state);
+            }
         }
     }
 }
 impl Svg {
-    /// load a svg icon from buffer
+    /// load a svg icon from buffer. gzip compressed `.svgz` data and `data:` uris
+    /// are detected and decoded
+    ///
+    /// panics if `data` is not a valid svg, which is kept for backward compatibility.
+    /// see [`Svg::try_new`] for a fallible version and [`Svg::new_with_options`]
+    /// to control fonts, dpi and the resource directory
     #[cfg_attr(feature = "cached", doc = "")]
-    #[cfg_attr(feature = "cached", doc = "`cached`: cached svg tree will never drop")]
+    #[cfg_attr(
+        feature = "cached",
+        doc = "`cached`: cached svg trees are kept until [`Svg::clear_cache`] is called \
+        or the capacity set with [`Svg::set_cache_capacity`] is exceeded"
+    )]
     #[cfg_attr(feature = "static_cached", doc = "")]
     #[cfg_attr(
         feature = "static_cached",
-        doc = "`static_cached`: using ptr as cache key so `data` must be `'static`"
+        doc = "`static_cached`: using ptr and length as cache key so `data` must be `'static`. \
+        the same svg included twice is parsed and cached twice, and different data that \
+        ends up at a reused address would hit the stale entry. \
+        see [`Svg::new_with_key`] and [`Svg::new_from_str`]"
     )]
+    #[doc(alias = "from_data_uri")]
     pub fn new(
         #[cfg(not(feature = "static_cached"))] data: &[u8],
         #[cfg(feature = "static_cached")] data: &'static [u8],
     ) -> Self {
-        #[cfg(feature = "puffin")]
-        puffin::profile_function!();
-
-        #[cfg(not(feature = "cached"))]
-        let tree = usvg::Tree::from_data(data, &usvg::Options::default()).unwrap();
-
-        #[cfg(feature = "cached")]
-        let tree = {
-            use egui::epaint::ahash::*;
-            use std::cell::RefCell;
-            use std::hash::*;
-            use std::rc::Rc;
-
-            thread_local! {
-                static CACHE: RefCell<HashMap<u64, Rc<usvg::Tree>>> = Default::default();
+        Self::try_new(data).unwrap()
+    }
+    /// load a svg icon from buffer, returning an error if `data` is not a valid svg
+    #[cfg_attr(feature = "cached", doc = "")]
+    #[cfg_attr(
+        feature = "cached",
+        doc = "`cached`: svg trees that fail to parse are not cached"
+    )]
+    pub fn try_new(
+        #[cfg(not(feature = "static_cached"))] data: &[u8],
+        #[cfg(feature = "static_cached")] data: &'static [u8],
+    ) -> Result<Self, SvgError> {
+        Self::try_new_with_options(data, &usvg::Options::default())
+    }
+    /// load a svg icon from buffer with the given parsing options
+    ///
+    /// panics if `data` is not a valid svg
+    #[cfg_attr(feature = "cached", doc = "")]
+    #[cfg_attr(
+        feature = "cached",
+        doc = "`cached`: `resources_dir`, `dpi`, `font_family`, `font_size`, `languages` \
+        and `default_size` are part of the cache key"
+    )]
+    pub fn new_with_options(
+        #[cfg(not(feature = "static_cached"))] data: &[u8],
+        #[cfg(feature = "static_cached")] data: &'static [u8],
+        options: &usvg::Options,
+    ) -> Self {
+        Self::try_new_with_options(data, options).unwrap()
+    }
+    /// load a svg icon from buffer with the given parsing options,
+    /// returning an error if `data` is not a valid svg
+    pub fn try_new_with_options(
+        #[cfg(not(feature = "static_cached"))] data: &[u8],
+        #[cfg(feature = "static_cached")] data: &'static [u8],
+        options: &usvg::Options,
+    ) -> Result<Self, SvgError> {
+        Ok(Self::from_svg_tree(load_tree(
+            data,
+            options,
+            TreeKey::Data,
+        )?))
+    }
+    /// load a svg icon from buffer, identified by `key` in the tree and mesh caches instead of
+    /// its content or address. `data` with the same key must have the same content, so this
+    /// shares one tree between copies of a svg and avoids hashing large files
+    ///
+    /// panics if `data` is not a valid svg
+    pub fn new_with_key(data: &[u8], key: u64) -> Self {
+        Self::try_new_with_key(data, key).unwrap()
+    }
+    /// like [`Svg::new_with_key`], returning an error if `data` is not a valid svg
+    pub fn try_new_with_key(data: &[u8], key: u64) -> Result<Self, SvgError> {
+        Ok(Self::from_svg_tree(load_tree(
+            data,
+            &usvg::Options::default(),
+            TreeKey::Custom(key),
+        )?))
+    }
+    /// load a svg icon from a string
+    ///
+    /// panics if `svg` is not a valid svg
+    #[cfg_attr(feature = "static_cached", doc = "")]
+    #[cfg_attr(
+        feature = "static_cached",
+        doc = "`static_cached`: the content is used as cache key since strings are often built at runtime"
+    )]
+    pub fn new_from_str(svg: &str) -> Self {
+        Self::try_from_str(svg).unwrap()
+    }
+    /// load a svg icon from a string, returning an error if `svg` is not a valid svg
+    pub fn try_from_str(svg: &str) -> Result<Self, SvgError> {
+        Ok(Self::from_svg_tree(load_tree(
+            svg.as_bytes(),
+            &usvg::Options::default(),
+            TreeKey::Content,
+        )?))
+    }
+    /// load a gzip compressed `.svgz` icon, returning an error if `data` isn't gzip data.
+    /// the other constructors detect svgz data as well
+    pub fn from_svgz(data: &[u8]) -> Result<Self, SvgError> {
+        let data = usvg::decompress_svgz(data)?;
+        Ok(Self::from_svg_tree(load_tree(
+            &data,
+            &usvg::Options::default(),
+            TreeKey::Content,
+        )?))
+    }
+    /// load a svg icon from a file. relative references are resolved against the file's directory
+    #[cfg_attr(feature = "cached", doc = "")]
+    #[cfg_attr(
+        feature = "cached",
+        doc = "`cached`: keyed on the file's contents so changed files are reloaded"
+    )]
+    #[doc(alias = "from_path")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, SvgError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        let options = usvg::Options {
+            resources_dir: std::fs::canonicalize(path)?
+                .parent()
+                .map(|p| p.to_path_buf()),
+            ..Default::default()
+        };
+        Ok(Self::from_svg_tree(load_tree(
+            &data,
+            &options,
+            TreeKey::Content,
+        )?))
+    }
+    /// load a svg icon from a file like [`Svg::from_file`], reloading it when the file's
+    /// modification time changes. the file is checked when the icon is shown, so keep the
+    /// `Svg` around and show clones of it. if the changed file fails to parse,
+    /// the last valid version is kept
+    #[cfg(feature = "hot_reload")]
+    #[doc(alias = "with_hot_reload")]
+    pub fn from_file_hot_reload(path: impl AsRef<std::path::Path>) -> Result<Self, SvgError> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut svg = Self::from_file(path)?;
+        svg.hot_reload = Some(std::rc::Rc::new(HotReload {
+            path: path.to_path_buf(),
+            modified: std::cell::Cell::new(modified),
+            tree: std::cell::RefCell::new(svg.tree.clone()),
+        }));
+        Ok(svg)
+    }
+    /// reload the file of [`Svg::from_file_hot_reload`] if it changed
+    #[cfg(feature = "hot_reload")]
+    fn hot_reload(&mut self) {
+        let Some(hot_reload) = &self.hot_reload else {
+            return;
+        };
+        let modified = std::fs::metadata(&hot_reload.path)
+            .and_then(|m| m.modified())
+            .ok();
+        if modified != hot_reload.modified.get() {
+            hot_reload.modified.set(modified);
+            if let Ok(svg) = Self::from_file(&hot_reload.path) {
+                let old = hot_reload.tree.replace(svg.tree);
+                #[cfg(feature = "cached")]
+                cache::remove(old.0);
+                #[cfg(not(feature = "cached"))]
+                let _ = old;
             }
-            CACHE.with(|cache| {
-                let key = {
-                    let mut hasher = RandomState::with_seed(0).build_hasher();
-
-                    #[cfg(not(feature = "static_cached"))]
-                    data.hash(&mut hasher);
-
-                    #[cfg(feature = "static_cached")]
-                    data.as_ptr().hash(&mut hasher);
-
-                    hasher.finish()
-                };
+        }
+        let tree = hot_reload.tree.borrow().clone();
+        if tree.0 != self.tree.0 {
+            self.tree = tree;
+            self.update_trim();
+        }
+    }
+    /// use an already parsed svg tree
+    ///
+    /// meshes are cached using the `Rc`'s ptr as key, so keep the `Rc` alive and pass clones
+    /// of it. see [`Svg::from_tree_with_key`]
+    pub fn from_tree(tree: impl Into<std::rc::Rc<usvg::Tree>>) -> Self {
+        let tree = tree.into();
+        let tree = {
+            use egui::epaint::ahash::RandomState;
 
-                (
-                    key,
-                    cache
-                        .borrow_mut()
-                        .entry(key)
-                        .or_insert_with(|| {
-                            Rc::new(usvg::Tree::from_data(data, &usvg::Options::default()).unwrap())
-                        })
-                        .clone(),
-                )
-            })
+            let key = RandomState::with_seed(0).hash_one(std::rc::Rc::as_ptr(&tree));
+            (key, tree, None)
         };
 
+        Self::from_svg_tree(tree)
+    }
+    /// use an already parsed svg tree with an explicit cache key.
+    /// `key` must be unique for each distinct tree
+    pub fn from_tree_with_key(tree: impl Into<std::rc::Rc<usvg::Tree>>, key: u64) -> Self {
+        Self::from_svg_tree((key, tree.into(), None))
+    }
+    fn from_svg_tree(tree: SvgTree) -> Self {
+        let align = to_egui_align(tree.1.view_box.aspect.align);
+
         Svg {
             tree,
             color_override: ColorOverride::None,
             background: Background::None,
             tolerance: 1.0,
             scale_tolerance: true,
+            pixel_tolerance: None,
             fit_mode: FitMode::Contain(Default::default()),
             sense: Sense::hover(),
+            id_source: None,
+            disabled_behavior: DisabledBehavior::Fade,
+            color_func: None,
+            color_key: None,
+            id_colors: Vec::new(),
+            align,
+            rotation: 0.0,
+            rtl_mirror: false,
+            flip: (false, false),
+            opacity: 1.0,
+            desaturate: 0.0,
+            inverted: false,
+            paint_fallback: None,
+            current_color: None,
+            #[cfg(feature = "raster")]
+            backend: Backend::Tessellate,
+            #[cfg(feature = "raster")]
+            supersample: 1,
+            #[cfg(feature = "gradient")]
+            gradient_subdivision: 16.0,
+            size_rounding: 0.0,
+            max_vertices: None,
+            paint_mode: PaintMode::FillAndStroke,
+            stroke_override: None,
+            vertex_budget: usize::MAX,
+            path_budget: usize::MAX,
+            viewport: None,
+            trim: false,
+            trim_bounds: None,
+            element: None,
+            text_style: None,
+            #[cfg(feature = "hot_reload")]
+            hot_reload: None,
         }
     }
-    /// set the tessellation tolerance
+    /// set the tessellation tolerance. values below `0.001`, including `0.0`, negative values
+    /// and NaN are raised to `0.001`
     pub fn with_tolerance(mut self, tolerance: f32) -> Self {
         self.tolerance = tolerance;
+        self.pixel_tolerance = None;
+        self
+    }
+    /// set the tessellation tolerance in physical pixels, so curves look equally smooth on
+    /// low and high dpi screens. the show methods divide it by the ui's `pixels_per_point`,
+    /// which makes meshes of different dpis separate cache entries. elsewhere, e.g. in
+    /// [`Svg::paint_at`] and [`Svg::to_mesh`], it's used as a tolerance in points.
+    /// enables [`Svg::with_scale_tolerance`]
+    pub fn with_pixel_tolerance(mut self, pixel_tolerance: f32) -> Self {
+        self.tolerance = pixel_tolerance;
+        self.scale_tolerance = true;
+        self.pixel_tolerance = Some(pixel_tolerance);
         self
     }
-    /// set whether the tessellation tolerance is affected by the scale
+    /// set the tessellation tolerance from a [`Quality`]. like [`Svg::with_tolerance`],
+    /// the tolerance is in screen points when [`Svg::with_scale_tolerance`] is on (the default),
+    /// otherwise in svg units
+    pub fn with_quality(self, quality: Quality) -> Self {
+        self.with_tolerance(quality.tolerance())
+    }
+    /// set whether the tessellation tolerance is affected by the scale.
+    /// for non uniform scales the larger axis is used, so the other axis may get more vertices
+    /// than needed
+    ///
+    /// enabled by default, which adapts the density to the drawn size: the tolerance is in
+    /// points, so a small toolbar icon gets far fewer vertices than the same svg drawn large.
+    /// the size is part of the mesh cache key, so each size gets its own mesh
     pub fn with_scale_tolerance(mut self, scale_tolerance: bool) -> Self {
         self.scale_tolerance = scale_tolerance;
         self
     }
+    /// derive the tolerance from the drawn size: `quality` is the tolerance in screen points,
+    /// divided by the larger axis of the scale into svg units. same as
+    /// `with_tolerance(quality).with_scale_tolerance(true)`, overriding both.
+    /// tolerances are clamped to a small positive minimum, see [`Svg::with_tolerance`]
+    pub fn with_auto_tolerance(self, quality: f32) -> Self {
+        self.with_tolerance(quality).with_scale_tolerance(true)
+    }
+    /// split triangles of gradient filled shapes until no edge is longer than `max_len` points,
+    /// as colors are only sampled at vertices. defaults to `16.0`, `0.0` disables it
+    #[cfg(feature = "gradient")]
+    pub fn with_gradient_subdivision(mut self, max_len: f32) -> Self {
+        self.gradient_subdivision = if max_len > 0.0 { max_len.max(1.0) } else { 0.0 };
+        self
+    }
+    /// set the color of paints that can't be tessellated, e.g. patterns.
+    /// defaults to the average color of a pattern's content, or black
+    ///
+    /// use `ui.visuals().text_color()` to follow the theme
+    #[cfg_attr(feature = "raster", doc = "")]
+    #[cfg_attr(
+        feature = "raster",
+        doc = "`raster`: [`Backend::Raster`] renders patterns properly"
+    )]
+    pub fn with_paint_fallback(mut self, color: Color32) -> Self {
+        self.paint_fallback = Some(color);
+        self
+    }
+    /// set how the icon is drawn. defaults to [`Backend::Tessellate`]
+    ///
+    /// with [`Backend::Raster`] color overrides tint the rendered texture,
+    /// [`Svg::with_texture`] replaces it, and gradient stops aren't color mapped.
+    /// [`Svg::to_mesh`] always tessellates as there is no context to upload textures to
+    #[cfg(feature = "raster")]
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+    /// render [`Backend::Raster`] textures at `factor` times the resolution and downscale them,
+    /// for smoother edges of thin shapes. defaults to `1`
+    ///
+    /// resvg output is already anti-aliased, while tessellated meshes rely on the renderer's
+    /// multisampling. without multisampling, prefer [`Backend::Raster`] for smooth edges
+    #[cfg(feature = "raster")]
+    pub fn with_supersample(mut self, factor: u32) -> Self {
+        self.supersample = factor.max(1);
+        self
+    }
+    /// set the color of `currentColor` in the svg. defaults to the ui's text color, or the
+    /// default style's text color for [`Svg::to_mesh`]. elements inside a `color` attribute
+    /// or style property keep using that color
+    ///
+    /// only applies to svgs parsed by this crate, not to [`Svg::from_tree`]
+    pub fn with_current_color(mut self, color: Color32) -> Self {
+        self.current_color = Some(color);
+        self
+    }
     /// override all elements' color
     pub fn with_color(mut self, color: Color32) -> Self {
         self.color_override = ColorOverride::Color(color);
         self
     }
+    /// override all elements' color with a color picked from the ui's visuals when drawn,
+    /// so the icon follows theme changes without being rebuilt
+    ///
+    /// [`Svg::show_sized`] uses the ui's visuals, painting without a ui uses the context's style
+    pub fn with_ui_color(
+        mut self,
+        color: impl Fn(&Visuals) -> Color32 + Send + Sync + 'static,
+    ) -> Self {
+        self.color_override = ColorOverride::FromVisuals(std::sync::Arc::new(color));
+        self
+    }
+    /// override all elements' color like [`Svg::with_color`], but keep each element's alpha
+    /// so translucent parts like shadows stay translucent
+    pub fn with_tint(mut self, color: Color32) -> Self {
+        self.color_override = ColorOverride::Tint(color);
+        self
+    }
     /// override all elements' color with given texture
     pub fn with_texture(mut self, texture: TextureId) -> Self {
         self.color_override = ColorOverride::Texture(texture);
@@ -191,7 +896,7 @@ impl Svg {
     ) -> Self {
         #[cfg(not(feature = "gradient"))]
         {
-            drop((colors, start, end, wrap_mode));
+            let _ = (colors, start, end, wrap_mode);
             self
         }
         #[cfg(feature = "gradient")]
@@ -203,19 +908,123 @@ impl Svg {
                     .copied()
                     .map(|(fac, color)| gradient::GradientColor { fac, color })
                     .collect(),
-                start,
-                end,
+                kind: gradient::GradientKind::Linear {
+                    start,
+                    end,
+                    transform: Default::default(),
+                },
                 wrap_mode,
             });
             svg
         }
     }
+    /// map each element's original svg color. the color is opaque and fill/stroke opacity
+    /// is applied to the alpha of the returned color. gradients map every stop color
+    ///
+    /// functions can't be compared, so the mesh is tessellated every frame unless it's
+    /// identified with [`Svg::with_color_key`] afterwards
+    pub fn with_color_map(
+        mut self,
+        color_func: impl Fn(Color32) -> Color32 + Send + Sync + 'static,
+    ) -> Self {
+        self.color_func = Some(std::sync::Arc::new(move |c: &ColorContext| {
+            color_func(c.color)
+        }));
+        self.color_key = None;
+        self
+    }
+    /// like [`Svg::with_color_map`] with more information about the element, e.g. to only
+    /// recolor strokes or the path with a certain id. replaces [`Svg::with_color_map`]
+    ///
+    /// like [`Svg::with_color_map`], the mesh is only cached with [`Svg::with_color_key`]
+    pub fn with_color_map_ex(
+        mut self,
+        color_func: impl Fn(&ColorContext) -> Color32 + Send + Sync + 'static,
+    ) -> Self {
+        self.color_func = Some(std::sync::Arc::new(color_func));
+        self.color_key = None;
+        self
+    }
+    /// replace exact original svg colors, e.g. brand colors with theme colors.
+    /// colors are matched before applying opacity, so translucent elements match too.
+    /// replaces [`Svg::with_color_map`] and sets [`Svg::with_color_key`] from the table
+    pub fn with_color_table(mut self, table: impl IntoIterator<Item = (Color32, Color32)>) -> Self {
+        use egui::epaint::ahash::RandomState;
+
+        let mut table = table.into_iter().collect::<Vec<_>>();
+        table.sort_by_key(|(from, _)| from.to_array());
+        self.color_key = Some(RandomState::with_seed(0).hash_one(&table));
+
+        let table = table
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>();
+        self.color_func = Some(std::sync::Arc::new(move |c: &ColorContext| {
+            table.get(&c.color).copied().unwrap_or(c.color)
+        }));
+        self
+    }
+    /// override the key identifying the svg tree in the mesh cache, e.g. for trees passed to
+    /// [`Svg::from_tree`]. the tree is already loaded, so use [`Svg::new_with_key`] to
+    /// pick the tree cache's key too. svgs with the same key must have the same content
+    pub fn with_cache_key(mut self, key: u64) -> Self {
+        self.tree.0 = key;
+        self
+    }
+    /// color the element with `id` and its children, like a color map that only applies to them.
+    /// takes precedence over [`Svg::with_color_map`]. see [`Svg::ids`] for the available ids
+    pub fn with_color_for_id(mut self, id: &str, color: Color32) -> Self {
+        self.id_colors.retain(|(other, _)| other != id);
+        self.id_colors.push((id.to_owned(), color));
+        self
+    }
+    /// identify the color map set with [`Svg::with_color_map`], so its meshes can be cached.
+    /// icons that only differ in their color map must use different keys
+    pub fn with_color_key(mut self, key: u64) -> Self {
+        self.color_key = Some(key);
+        self
+    }
+    /// override all elements' color depending on the interaction state
+    pub fn with_state_colors(
+        mut self,
+        normal: Color32,
+        hovered: Color32,
+        active: Color32,
+        disabled: Color32,
+    ) -> Self {
+        self.color_override = ColorOverride::State {
+            normal,
+            hovered,
+            active,
+            disabled,
+        };
+        self
+    }
+    /// override all elements' color, easing from `from` to `to` while `active` and back
+    /// otherwise. uses the style's animation time and requests repaints while animating.
+    /// `id_source` must be stable across frames, e.g. the response id of a hovered button
+    ///
+    /// [`Svg::to_mesh`] uses the end color as there is no context to animate with
+    pub fn with_color_animated(
+        mut self,
+        id_source: impl std::hash::Hash,
+        from: Color32,
+        to: Color32,
+        active: bool,
+    ) -> Self {
+        self.color_override = ColorOverride::Animated {
+            id: Id::new(id_source),
+            from,
+            to,
+            active,
+        };
+        self
+    }
     /// override all elements' color with fg_stroke
     pub fn with_color_from_style(mut self) -> Self {
         self.color_override = ColorOverride::FromStyle;
         self
     }
-    /// set background
+    /// paint a background into the whole frame behind the icon, e.g. for chips and badges
     pub fn with_background(mut self, rounding: Rounding, fill: Color32, stroke: Stroke) -> Self {
         self.background = Background::Custom {
             fill,
@@ -234,39 +1043,502 @@ impl Svg {
         self.fit_mode = fit_mode;
         self
     }
-    /// set response sense
+    /// multiply the alpha of the whole icon by `opacity`, clamped to `0.0..=1.0`.
+    /// applied after all other color changes
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+    /// lerp the colors of the whole icon towards their luminance, clamped to `0.0..=1.0`.
+    /// `1.0` is fully grayscale, `0.0` leaves the colors untouched.
+    /// applied before the color overrides, so a tint colors the grayscale icon
+    pub fn with_desaturate(mut self, amount: f32) -> Self {
+        self.desaturate = amount.clamp(0.0, 1.0);
+        self
+    }
+    /// show the icon fully grayscale, see [`Svg::with_desaturate`]
+    pub fn grayscale(self) -> Self {
+        self.with_desaturate(1.0)
+    }
+    /// invert the colors of the whole icon keeping their alpha, e.g. for dark glyphs on
+    /// dark themes. applied before [`Svg::with_desaturate`] and the color overrides
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+    /// set how the icon looks in a disabled ui. defaults to [`DisabledBehavior::Fade`].
+    /// ignored with [`Svg::with_state_colors`]
+    pub fn with_disabled_behavior(mut self, disabled_behavior: DisabledBehavior) -> Self {
+        self.disabled_behavior = disabled_behavior;
+        self
+    }
+    /// set how the shape is aligned inside the frame. defaults to the svg's
+    /// `preserveAspectRatio` alignment, which is [`Align2::CENTER_CENTER`] if not set
+    ///
+    /// with [`FitMode::Contain`] the shape is aligned inside the frame minus the margin.
+    /// with [`FitMode::Cover`] the alignment decides which part of the shape is clipped
+    pub fn with_align(mut self, align: Align2) -> Self {
+        self.align = align;
+        self
+    }
+    /// rotate the shape around the center of where it's placed, in radians
+    ///
+    /// the shape is rotated within its unrotated bounds so corners may reach outside the frame.
+    /// the rotation is applied to the tessellated mesh, so animating it doesn't retessellate
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    /// mirror the shape around the center of where it's placed
+    pub fn with_flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.flip = (horizontal, vertical);
+        self
+    }
+    /// mirror the shape horizontally when the ui's layout prefers right to left
+    pub fn with_rtl_mirror(mut self, rtl_mirror: bool) -> Self {
+        self.rtl_mirror = rtl_mirror;
+        self
+    }
+    /// round the tessellated size to multiples of `size_rounding` and scale the mesh
+    /// to the exact size, so small size changes (e.g. while resizing) reuse the cached mesh.
+    /// the meshes of the most recently used sizes are kept for a while after they're unused,
+    /// so a size going back and forth doesn't tessellate again.
+    /// `0.0` disables rounding, which is the default
+    pub fn with_size_rounding(mut self, size_rounding: f32) -> Self {
+        self.size_rounding = size_rounding;
+        self
+    }
+    /// split the painted mesh into meshes of at most `max_vertices` vertices,
+    /// for renderers that only support 16 bit indices. unlimited by default
+    pub fn with_max_vertices_per_mesh(mut self, max_vertices: usize) -> Self {
+        self.max_vertices = Some(max_vertices);
+        self
+    }
+    /// only draw fills or strokes, e.g. outlines for a ghost state.
+    /// defaults to [`PaintMode::FillAndStroke`]
+    #[cfg_attr(feature = "raster", doc = "")]
+    #[cfg_attr(feature = "raster", doc = "`raster`: ignored by [`Backend::Raster`]")]
+    pub fn with_paint_mode(mut self, paint_mode: PaintMode) -> Self {
+        self.paint_mode = paint_mode;
+        self
+    }
+    /// stroke every path with `color` and `width` in points instead of its own stroke,
+    /// e.g. for focus rings or selection highlights. the width doesn't scale with the icon
+    /// and the color isn't affected by color maps
+    #[cfg_attr(feature = "raster", doc = "")]
+    #[cfg_attr(feature = "raster", doc = "`raster`: ignored by [`Backend::Raster`]")]
+    pub fn with_stroke_override(mut self, color: Color32, width: f32) -> Self {
+        self.stroke_override = Some((color, width));
+        self
+    }
+    /// stop tessellating once the mesh would have more than `vertex_budget` vertices, e.g. to
+    /// guard against huge untrusted svgs. the path exceeding the budget and all following ones
+    /// are dropped, see [`SvgResponse::truncated`]. unlimited by default
+    ///
+    /// the path exceeding the budget is still tessellated before being dropped
+    pub fn with_vertex_budget(mut self, vertex_budget: usize) -> Self {
+        self.vertex_budget = vertex_budget;
+        self
+    }
+    /// only tessellate the first `path_budget` paths, see [`Svg::with_vertex_budget`]
+    pub fn with_path_budget(mut self, path_budget: usize) -> Self {
+        self.path_budget = path_budget;
+        self
+    }
+    /// only show `viewport` (in svg units) instead of the whole viewbox.
+    /// the shape is fitted as if `viewport` was its viewbox and geometry outside is clipped
+    ///
+    /// useful to show a single icon from a sprite sheet
+    #[cfg_attr(feature = "image", doc = "")]
+    #[cfg_attr(
+        feature = "image",
+        doc = "`image`: embedded raster images are only clipped to the frame"
+    )]
+    pub fn with_viewport(mut self, viewport: Rect) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+    /// fit the drawn shapes instead of the view box, trimming its padding so icons of a set
+    /// have a similar visual weight. combine with [`FitMode::Contain`] to add a margin.
+    /// uses [`Svg::content_bounds`] as the viewport, or keeps the view box or viewport
+    /// if nothing is drawn
+    ///
+    /// trims the element of [`Svg::with_element`] if there is one.
+    /// the view box or viewport is used again after `with_trim(false)`
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self.update_trim();
+        self
+    }
+    /// only show the element with `id` and its children, e.g. a single icon of a sprite sheet.
+    /// its bounding box is used as the viewport, see [`Svg::with_viewport`]
+    ///
+    /// nothing is shown if there is no such element
+    pub fn with_element(mut self, id: &str) -> Self {
+        use usvg::NodeExt;
+
+        let tree = &self.tree.1;
+
+        let node = tree.node_by_id(id);
+        debug_assert!(node.is_some(), "no svg element with id {id:?}");
+        self.viewport = node
+            .and_then(|node| node.calculate_bbox())
+            .and_then(|bbox| bbox.to_rect())
+            .map(to_egui_rect);
+        self.element = Some(id.to_owned());
+        self.update_trim();
+        self
+    }
+    /// text style whose line height [`Svg::show_inline`] matches
+    pub fn with_text_style(mut self, text_style: TextStyle) -> Self {
+        self.text_style = Some(text_style);
+        self
+    }
+    /// set response sense. defaults to [`Sense::hover`]
+    ///
+    /// combine with [`Svg::with_background_from_style`] to highlight clickable icons on hover
     pub fn with_sense(mut self, sense: Sense) -> Self {
         self.sense = sense;
         self
     }
+    /// set a stable id source for the response instead of the ui's auto id
+    pub fn with_id_source(mut self, id_source: impl std::hash::Hash) -> Self {
+        self.id_source = Some(Id::new(id_source));
+        self
+    }
     /// show the icon at the svg's original size
-    pub fn show(self, ui: &mut Ui) -> Response {
+    pub fn show(self, ui: &mut Ui) -> SvgResponse {
+        let size = self.original_size();
+        self.show_sized(ui, size)
+    }
+    /// size used by [`Widget::ui`]. the svg's original size like [`Svg::show`],
+    /// scaled down to fit if it is larger than `available`
+    pub fn desired_size(&self, available: Vec2) -> Vec2 {
+        let size = self.original_size();
+        let factor = (available / size).min_elem();
+        if factor.is_finite() && factor > 0.0 && factor < 1.0 {
+            size * factor
+        } else {
+            size
+        }
+    }
+    fn original_size(&self) -> Vec2 {
         let mut size = self.svg_rect().size();
-        if let FitMode::Contain(m) = self.fit_mode {
+        if let FitMode::Contain(m) | FitMode::ScaleDown(m) = self.fit_mode {
             size += m.sum();
         }
-        self.show_sized(ui, size)
+        size
     }
     /// show the icon. size is based on available height of the ui
-    pub fn show_justified(self, ui: &mut Ui) -> Response {
-        let size = [
-            ui.available_height() * self.svg_rect().aspect_ratio(),
+    ///
+    /// without available height (e.g. in the first frame of a shrinking scroll area)
+    /// the body text height is used, and the svg's height if it's unbounded
+    pub fn show_justified(self, ui: &mut Ui) -> SvgResponse {
+        let height = justified_length(
             ui.available_height(),
-        ];
+            ui.text_style_height(&TextStyle::Body),
+            self.svg_rect().height(),
+        );
+        let size = Vec2::new(height * self.svg_rect().aspect_ratio(), height);
         self.show_sized(ui, size)
     }
+    /// show the icon. size is based on available width of the ui, e.g. in vertical layouts.
+    /// see [`Svg::show_justified`]
+    pub fn show_justified_width(self, ui: &mut Ui) -> SvgResponse {
+        let width = justified_length(
+            ui.available_width(),
+            ui.text_style_height(&TextStyle::Body),
+            self.svg_rect().width(),
+        );
+        let size = Vec2::new(width, width / self.svg_rect().aspect_ratio());
+        self.show_sized(ui, size)
+    }
+    /// show the icon next to a label in a horizontal layout. the frame is as high as a line of
+    /// text and the icon sits on the text's baseline, as high as the text above it.
+    /// the text style defaults to [`TextStyle::Body`], see [`Svg::with_text_style`]
+    pub fn show_inline(self, ui: &mut Ui) -> SvgResponse {
+        let text_style = self.text_style.clone().unwrap_or(TextStyle::Body);
+        let font_id = text_style.resolve(ui.style());
+        let (row_height, baseline) = ui.fonts(|fonts| {
+            let row_height = fonts.row_height(&font_id);
+            (
+                row_height,
+                text_baseline(fonts, font_id).clamp(0.0, row_height),
+            )
+        });
+        let size = Vec2::new(baseline * self.svg_rect().aspect_ratio(), row_height);
+        self.show_in_frame(ui, size, |frame_rect| {
+            Rect::from_min_size(frame_rect.min, Vec2::new(frame_rect.width(), baseline))
+        })
+    }
     /// show the icon at the given size
-    pub fn show_sized(self, ui: &mut Ui, size: impl Into<Vec2>) -> Response {
+    pub fn show_sized(self, ui: &mut Ui, size: impl Into<Vec2>) -> SvgResponse {
+        self.show_in_frame(ui, size.into(), |frame_rect| frame_rect)
+    }
+    /// allocate a frame of `size` and paint the icon in the part of it returned by `icon_frame`
+    fn show_in_frame(
+        mut self,
+        ui: &mut Ui,
+        size: Vec2,
+        icon_frame: impl FnOnce(Rect) -> Rect,
+    ) -> SvgResponse {
         #[cfg(feature = "puffin")]
         puffin::profile_function!();
 
-        let size = size.into();
+        self.prepare_for_ui(ui);
         let (id, frame_rect) = ui.allocate_space(size);
+        let id = self
+            .id_source
+            .map_or(id, |id_source| ui.make_persistent_id(id_source));
+        let response = ui.interact(frame_rect, id, self.sense);
+        let (icon_rect, truncated) = self.paint(
+            ui.painter(),
+            icon_frame(frame_rect),
+            ui.style().interact(&response),
+            Some(&response),
+            ui.layout().prefer_right_to_left(),
+        );
+        SvgResponse {
+            response,
+            frame_rect,
+            icon_rect,
+            truncated,
+        }
+    }
+    /// show the icon in the given rect without allocating space in the ui
+    pub fn paint_in_ui_at(&self, ui: &mut Ui, rect: Rect) -> SvgResponse {
+        #[cfg(feature = "puffin")]
+        puffin::profile_function!();
+
+        let mut svg = self.clone();
+        svg.prepare_for_ui(ui);
+        let id = match svg.id_source {
+            Some(id_source) => ui.make_persistent_id(id_source),
+            None => {
+                let id = ui.next_auto_id();
+                ui.skip_ahead_auto_ids(1);
+                id
+            }
+        };
+        let response = ui.interact(rect, id, svg.sense);
+        let (icon_rect, truncated) = svg.paint(
+            ui.painter(),
+            rect,
+            ui.style().interact(&response),
+            Some(&response),
+            ui.layout().prefer_right_to_left(),
+        );
+        SvgResponse {
+            response,
+            frame_rect: rect,
+            icon_rect,
+            truncated,
+        }
+    }
+    /// paint the icon into the given rect. styles are taken from inactive widgets.
+    /// returns where the icon is drawn
+    pub fn paint_at(&self, painter: &Painter, rect: Rect) -> Rect {
+        #[cfg(feature = "puffin")]
+        puffin::profile_function!();
+
+        let style = painter.ctx().style();
+        self.paint(painter, rect, &style.visuals.widgets.inactive, None, false)
+            .0
+    }
+    /// resolve the settings that depend on the ui before painting in it: reload the file,
+    /// convert the pixel tolerance and take the current color and visuals' color from the ui
+    fn prepare_for_ui(&mut self, ui: &Ui) {
+        #[cfg(feature = "hot_reload")]
+        self.hot_reload();
+        if let Some(pixel_tolerance) = self.pixel_tolerance {
+            self.tolerance = pixel_tolerance / ui.ctx().pixels_per_point();
+        }
+        self.current_color.get_or_insert(ui.visuals().text_color());
+        if let ColorOverride::FromVisuals(color) = &self.color_override {
+            self.color_override = ColorOverride::Color(color(ui.visuals()));
+        }
+    }
+    /// returns the icon rect and whether the mesh was truncated
+    fn paint(
+        &self,
+        painter: &Painter,
+        frame_rect: Rect,
+        visuals: &style::WidgetVisuals,
+        response: Option<&Response>,
+        right_to_left: bool,
+    ) -> (Rect, bool) {
+        let rect = self.icon_rect(frame_rect);
+
+        #[cfg(feature = "culled")]
+        if !painter
+            .clip_rect()
+            .intersects(self.painted_bounds(rect, frame_rect))
+        {
+            return (rect, false);
+        }
+
+        let current_color = self
+            .current_color
+            .unwrap_or_else(|| painter.ctx().style().visuals.text_color());
+        let mut placed = self.tessellate_cached(Some(painter.ctx()), rect, current_color);
+        let mut shape = placed.take_mesh();
+        let tessellation = &*placed.tessellation;
+        self.filter_colors(&mut shape);
+        self.override_color(
+            &mut shape,
+            rect,
+            Some(painter.ctx()),
+            &painter.ctx().style().visuals,
+            visuals.fg_stroke.color,
+            response,
+        );
+        if response.is_some_and(|r| !r.enabled)
+            && !matches!(self.color_override, ColorOverride::State { .. })
+        {
+            match self.disabled_behavior {
+                DisabledBehavior::Fade => {
+                    let target = painter.ctx().style().visuals.weak_text_color();
+                    shape
+                        .vertices
+                        .iter_mut()
+                        .for_each(|v| v.color = fade_color(v.color, target, 0.5));
+                }
+                DisabledBehavior::Grayscale => shape
+                    .vertices
+                    .iter_mut()
+                    .for_each(|v| v.color = desaturate_color(v.color, 1.0)),
+                DisabledBehavior::None => {}
+            }
+        }
+        self.apply_opacity(&mut shape);
+
+        match &self.background {
+            Background::None => {}
+            Background::FromStyle => {
+                painter.rect(
+                    frame_rect,
+                    visuals.rounding,
+                    visuals.bg_fill,
+                    visuals.bg_stroke,
+                );
+            }
+            Background::Custom {
+                fill,
+                rounding,
+                stroke,
+            } => painter.rect(frame_rect, *rounding, *fill, *stroke),
+        }
+
+        // embedded images are painted between the shapes around them in the document
+        #[cfg(feature = "image")]
+        let layers = {
+            // resvg already renders embedded images
+            #[cfg(feature = "raster")]
+            let render_images = self.backend == Backend::Tessellate;
+            #[cfg(not(feature = "raster"))]
+            let render_images = true;
+            let images = if render_images {
+                raster::image_meshes(
+                    self,
+                    painter.ctx(),
+                    rect,
+                    rect.size() / self.svg_rect().size(),
+                )
+            } else {
+                Vec::new()
+            };
+            let images = images.into_iter().map(|image| {
+                image.map(|mut mesh| {
+                    self.filter_colors(&mut mesh);
+                    self.apply_opacity(&mut mesh);
+                    mesh
+                })
+            });
+            raster::interleave_images(shape, &tessellation.image_breaks, images)
+        };
+        #[cfg(not(feature = "image"))]
+        let layers = [shape];
+
+        for mut mesh in layers {
+            self.transform(&mut mesh, rect, right_to_left);
+            self.tile(&mut mesh, frame_rect);
+            match self.max_vertices {
+                Some(max_vertices) => painter
+                    .with_clip_rect(frame_rect)
+                    .extend(split_mesh(mesh, max_vertices).into_iter().map(Shape::mesh)),
+                None => {
+                    painter.with_clip_rect(frame_rect).add(mesh);
+                }
+            }
+        }
+
+        (rect, tessellation.stats.truncated)
+    }
+    /// tessellate the icon into a mesh fitted into `rect` using the fit mode
+    ///
+    /// [`Svg::with_color_from_style`] uses the default widget style as there is no ui.
+    /// embedded raster images are not part of the mesh
+    pub fn to_mesh(&self, rect: Rect) -> Mesh {
+        let icon_rect = self.icon_rect(rect);
+        let current_color = self
+            .current_color
+            .unwrap_or_else(|| Visuals::default().text_color());
+        let mut mesh = self
+            .tessellate_cached(None, icon_rect, current_color)
+            .take_mesh();
+        self.filter_colors(&mut mesh);
+        self.override_color(
+            &mut mesh,
+            icon_rect,
+            None,
+            &Visuals::default(),
+            Visuals::default().widgets.inactive.fg_stroke.color,
+            None,
+        );
+        self.apply_opacity(&mut mesh);
+        self.transform(&mut mesh, icon_rect, false);
+        self.tile(&mut mesh, rect);
+        mesh
+    }
+    /// tessellate the icon at `size` and count the vertices, indices and paths of the mesh,
+    /// e.g. to choose a tolerance. nothing is cached
+    pub fn stats(&self, size: impl Into<Vec2>) -> TessellationStats {
+        let size = size.into();
+        if size.min_elem() <= 0.0 || !size.is_finite() {
+            return TessellationStats::default();
+        }
+        let current_color = self
+            .current_color
+            .unwrap_or_else(|| Visuals::default().text_color());
+        tessellation::tessellate(
+            self,
+            Rect::from_min_size(Pos2::ZERO, size),
+            size / self.svg_rect().size(),
+            current_color,
+        )
+        .stats
+    }
+    /// tessellate the icon into a mesh of the given size with its top left corner at the origin.
+    /// see [`Svg::svg_rect`] for the size of the view box
+    ///
+    /// to draw the same icon many times per frame, bake it once and add
+    /// [`Mesh::translate`]d clones of it, skipping the cache lookup and color overrides
+    #[doc(alias = "bake")]
+    pub fn to_mesh_sized(&self, size: impl Into<Vec2>) -> Mesh {
+        self.to_mesh(Rect::from_min_size(Pos2::ZERO, size.into()))
+    }
+    /// where the icon is placed inside `frame_rect` according to the fit mode
+    fn icon_rect(&self, frame_rect: Rect) -> Rect {
+        if let FitMode::Tile { tile_size, .. } = self.fit_mode {
+            return Rect::from_min_size(frame_rect.min, tile_size);
+        }
         let mut inner_frame_rect = frame_rect;
         let size = match self.fit_mode {
             FitMode::None => self.svg_rect().size(),
             FitMode::Size(s) => s,
             FitMode::Factor(f) => self.svg_rect().size() * f,
+            FitMode::Stretch => frame_rect.size(),
+            FitMode::Tile { tile_size, .. } => tile_size,
             FitMode::Cover => Vec2::from(
                 if frame_rect.aspect_ratio() > self.svg_rect().aspect_ratio() {
                     [
@@ -280,10 +1552,10 @@ impl Svg {
                     ]
                 },
             ),
-            FitMode::Contain(margin) => {
+            FitMode::Contain(margin) | FitMode::ScaleDown(margin) => {
                 inner_frame_rect.min += margin.left_top();
                 inner_frame_rect.max -= margin.right_bottom();
-                Vec2::from(
+                let size = Vec2::from(
                     if inner_frame_rect.aspect_ratio() > self.svg_rect().aspect_ratio() {
                         [
                             self.svg_rect().width() * inner_frame_rect.height()
@@ -297,55 +1569,197 @@ impl Svg {
                                 / self.svg_rect().width(),
                         ]
                     },
-                )
+                );
+                if matches!(self.fit_mode, FitMode::ScaleDown(_))
+                    && size.x > self.svg_rect().width()
+                {
+                    self.svg_rect().size()
+                } else {
+                    size
+                }
             }
         };
-        let rect = Align2::CENTER_CENTER.align_size_within_rect(size, inner_frame_rect);
-        let response = ui.interact(frame_rect, id, self.sense);
-
-        #[cfg(feature = "culled")]
-        if !ui.clip_rect().intersects(rect) {
-            return response;
+        self.align.align_size_within_rect(size, inner_frame_rect)
+    }
+    /// tessellate into `rect`. meshes are cached in `ctx`'s memory if given
+    fn tessellate_cached(
+        &self,
+        ctx: Option<&Context>,
+        rect: Rect,
+        current_color: Color32,
+    ) -> tessellation::PlacedTessellation {
+        #[cfg(feature = "raster")]
+        if let (Backend::Raster, Some(ctx)) = (self.backend, ctx) {
+            return tessellation::Tessellation {
+                mesh: rasterize::raster_mesh(self, ctx, rect, current_color),
+                stats: Default::default(),
+                image_breaks: Vec::new(),
+            }
+            .into();
         }
 
-        #[cfg(not(feature = "cached"))]
-        let mut shape = tessellation::tessellate(&self, rect, size / self.svg_rect().size());
+        let size = rect.size();
+        // nothing to draw, and the tolerance would be infinite
+        if size.min_elem() <= 0.0 || !size.is_finite() {
+            return tessellation::Tessellation {
+                mesh: Mesh::default(),
+                stats: Default::default(),
+                image_breaks: Vec::new(),
+            }
+            .into();
+        }
 
-        #[cfg(feature = "cached")]
-        let mut shape = {
-            use egui::util::cache::*;
+        let placed = {
             use std::hash::*;
 
-            #[derive(Clone, Copy)]
-            struct TessellateCacheKey<'l>(&'l Svg, Vec2);
+            struct TessellateCacheKey<'l>(&'l Svg, Vec2, Color32);
             impl Hash for TessellateCacheKey<'_> {
                 fn hash<H: Hasher>(&self, state: &mut H) {
-                    let TessellateCacheKey(svg, size) = self;
+                    let TessellateCacheKey(svg, size, current_color) = self;
                     svg.hash(state);
                     bytes!(*size, Vec2).hash(state);
+                    current_color.hash(state);
                 }
             }
 
-            #[derive(Default)]
-            struct Tessellator;
-            impl ComputerMut<TessellateCacheKey<'_>, Mesh> for Tessellator {
-                fn compute(&mut self, TessellateCacheKey(svg, size): TessellateCacheKey) -> Mesh {
-                    tessellation::tessellate(
-                        svg,
-                        Rect::from_min_size(Pos2::ZERO, size),
-                        size / svg.svg_rect().size(),
-                    )
+            match ctx.filter(|_| self.cacheable_colors()) {
+                Some(ctx) => {
+                    let rounded = self.size_rounding > 0.0;
+                    let cached_size = if rounded {
+                        ((size / self.size_rounding).round() * self.size_rounding)
+                            .max(Vec2::splat(self.size_rounding))
+                    } else {
+                        size
+                    };
+                    let key =
+                        egui::util::hash(TessellateCacheKey(self, cached_size, current_color));
+                    let cached = ctx.memory_mut(|mem| {
+                        mem.caches
+                            .cache::<tessellation::MeshCache>()
+                            .get_or_insert_with(key, rounded, || {
+                                tessellation::tessellate(
+                                    self,
+                                    Rect::from_min_size(Pos2::ZERO, cached_size),
+                                    cached_size / self.svg_rect().size(),
+                                    current_color,
+                                )
+                            })
+                    });
+                    tessellation::PlacedTessellation {
+                        tessellation: cached,
+                        factor: size / cached_size,
+                        offset: rect.min.to_vec2(),
+                    }
                 }
+                None => tessellation::tessellate(
+                    self,
+                    rect,
+                    size / self.svg_rect().size(),
+                    current_color,
+                )
+                .into(),
             }
+        };
 
-            let mut mesh = ui.memory_mut(|mem| {
-                mem.caches
-                    .cache::<FrameCache<_, Tessellator>>()
-                    .get(TessellateCacheKey(&self, size))
-            });
-            mesh.translate(rect.min.to_vec2());
-            mesh
+        placed
+    }
+    /// repeat a mesh of the first tile to cover `frame_rect` with [`FitMode::Tile`]
+    fn tile(&self, mesh: &mut Mesh, frame_rect: Rect) {
+        let FitMode::Tile { tile_size, spacing } = self.fit_mode else {
+            return;
         };
+        let step = tile_size + spacing;
+        if step.min_elem() <= 0.0 {
+            return;
+        }
+        let [columns, rows] = [
+            (frame_rect.width() / step.x).ceil().max(1.0) as usize,
+            (frame_rect.height() / step.y).ceil().max(1.0) as usize,
+        ];
+
+        let tile = std::mem::take(mesh);
+        mesh.texture_id = tile.texture_id;
+        mesh.reserve_triangles(tile.indices.len() / 3 * columns * rows);
+        mesh.reserve_vertices(tile.vertices.len() * columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let offset = Vec2::new(column as f32 * step.x, row as f32 * step.y);
+                let mut copy = tile.clone();
+                copy.translate(offset);
+                mesh.append(copy);
+            }
+        }
+    }
+    /// mirror and rotate a mesh tessellated into `rect` around its center
+    fn transform(&self, mesh: &mut Mesh, rect: Rect, right_to_left: bool) {
+        let (horizontal, vertical) = self.flip;
+        if horizontal != (self.rtl_mirror && right_to_left) {
+            mesh.vertices
+                .iter_mut()
+                .for_each(|v| v.pos.x = 2.0 * rect.center().x - v.pos.x);
+        }
+        if vertical {
+            mesh.vertices
+                .iter_mut()
+                .for_each(|v| v.pos.y = 2.0 * rect.center().y - v.pos.y);
+        }
+        if self.rotation != 0.0 {
+            mesh.rotate(emath::Rot2::from_angle(self.rotation), rect.center());
+        }
+    }
+    /// bounds of what's painted for the icon in `rect`: the whole frame for tiles,
+    /// and the rotated icon rect otherwise
+    #[cfg(feature = "culled")]
+    fn painted_bounds(&self, rect: Rect, frame_rect: Rect) -> Rect {
+        if let FitMode::Tile { .. } = self.fit_mode {
+            return frame_rect;
+        }
+        if self.rotation == 0.0 {
+            return rect;
+        }
+        let rotation = emath::Rot2::from_angle(self.rotation);
+        Rect::from_points(
+            &[
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ]
+            .map(|p| rect.center() + rotation * (p - rect.center())),
+        )
+    }
+    /// apply [`Svg::with_inverted`] and [`Svg::with_desaturate`] to the mesh's colors
+    fn filter_colors(&self, mesh: &mut Mesh) {
+        if self.inverted {
+            mesh.vertices
+                .iter_mut()
+                .for_each(|v| v.color = invert_color(v.color));
+        }
+        if self.desaturate > 0.0 {
+            mesh.vertices
+                .iter_mut()
+                .for_each(|v| v.color = desaturate_color(v.color, self.desaturate));
+        }
+    }
+    /// apply [`Svg::with_opacity`] to the mesh's colors
+    fn apply_opacity(&self, mesh: &mut Mesh) {
+        if self.opacity < 1.0 {
+            mesh.vertices
+                .iter_mut()
+                .for_each(|v| v.color = scale_color(v.color, self.opacity));
+        }
+    }
+    /// apply the color override to a mesh tessellated into `rect`
+    /// `response` picks the color for [`Svg::with_state_colors`]
+    fn override_color(
+        &self,
+        mesh: &mut Mesh,
+        rect: Rect,
+        ctx: Option<&Context>,
+        ui_visuals: &Visuals,
+        style_color: Color32,
+        response: Option<&Response>,
+    ) {
         macro_rules! svg_pos {
             ($v:expr) => {
                 (($v.pos - rect.min) * (self.svg_rect().size() / rect.size())
@@ -356,57 +1770,843 @@ impl Svg {
         match &self.color_override {
             ColorOverride::None => {}
             ColorOverride::FromStyle => {
-                shape
-                    .vertices
-                    .iter_mut()
-                    .for_each(|v| v.color = ui.style().interact(&response).fg_stroke.color);
+                mesh.vertices.iter_mut().for_each(|v| v.color = style_color);
+            }
+            ColorOverride::Color(c) => mesh.vertices.iter_mut().for_each(|v| v.color = *c),
+            ColorOverride::FromVisuals(color) => {
+                let color = color(ui_visuals);
+                mesh.vertices.iter_mut().for_each(|v| v.color = color);
             }
-            ColorOverride::Color(c) => shape.vertices.iter_mut().for_each(|v| v.color = *c),
+            ColorOverride::Tint(c) => mesh
+                .vertices
+                .iter_mut()
+                .for_each(|v| v.color = scale_color(*c, v.color.a() as f32 / 255.0)),
             ColorOverride::Texture(t) => {
-                shape.texture_id = *t;
-                shape.vertices.iter_mut().for_each(|v| {
+                mesh.texture_id = *t;
+                mesh.vertices.iter_mut().for_each(|v| {
                     v.color = Color32::WHITE;
                     v.uv = (svg_pos!(v).to_vec2() / self.svg_rect().size()).to_pos2();
                 });
             }
             #[cfg(feature = "gradient")]
             ColorOverride::Gradient(g) => {
-                shape
-                    .vertices
+                mesh.vertices
                     .iter_mut()
                     .for_each(|v| v.color = g.color_at_pos(svg_pos!(v)));
             }
+            ColorOverride::State {
+                normal,
+                hovered,
+                active,
+                disabled,
+            } => {
+                let color = match response {
+                    Some(r) if !r.enabled => *disabled,
+                    Some(r) if r.is_pointer_button_down_on() => *active,
+                    Some(r) if r.hovered() => *hovered,
+                    _ => *normal,
+                };
+                mesh.vertices.iter_mut().for_each(|v| v.color = color);
+            }
+            ColorOverride::Animated {
+                id,
+                from,
+                to,
+                active,
+            } => {
+                let t = match ctx {
+                    Some(ctx) => ctx.animate_bool(*id, *active),
+                    None if *active => 1.0,
+                    None => 0.0,
+                };
+                let color = Color32::from(lerp(Rgba::from(*from)..=Rgba::from(*to), t));
+                mesh.vertices.iter_mut().for_each(|v| v.color = color);
+            }
         };
-
-        match &self.background {
-            Background::None => {}
-            Background::FromStyle => {
-                let visual = ui.style().interact(&response);
-                ui.painter().rect(
-                    frame_rect,
-                    visual.rounding,
-                    visual.bg_fill,
-                    visual.bg_stroke,
-                );
+    }
+    /// drop all cached svg trees of the current thread
+    #[cfg(feature = "cached")]
+    pub fn clear_cache() {
+        cache::clear();
+    }
+    /// number of cached svg trees of the current thread
+    #[cfg(feature = "cached")]
+    pub fn cache_len() -> usize {
+        cache::len()
+    }
+    /// limit the number of cached svg trees of the current thread,
+    /// evicting the least recently used ones. `None` means unbounded, which is the default
+    #[cfg(feature = "cached")]
+    pub fn set_cache_capacity(capacity: Option<usize>) {
+        cache::set_capacity(capacity);
+    }
+    /// original size of the svg shape, which is the size of [`Svg::svg_rect`].
+    /// used for [`FitMode::None`] and [`Svg::desired_size`]
+    #[doc(alias = "intrinsic_size")]
+    pub fn size(&self) -> Vec2 {
+        self.svg_rect().size()
+    }
+    /// aspect ratio (width / height) of the svg shape
+    pub fn aspect_ratio(&self) -> f32 {
+        self.svg_rect().aspect_ratio()
+    }
+    /// bounding box of the drawn shapes in svg units, including strokes. `None` if nothing
+    /// is drawn. useful to trim the padding around glyphs in their view box.
+    /// embedded images are not included
+    pub fn content_bounds(&self) -> Option<Rect> {
+        fn collect(
+            nodes: impl Iterator<Item = usvg::Node>,
+            transform: usvg::Transform,
+            bounds: &mut Rect,
+        ) {
+            for node in nodes {
+                match &*node.borrow() {
+                    usvg::NodeKind::Path(p) if p.visibility == usvg::Visibility::Visible => {
+                        let Some(bbox) = p.data.bbox() else {
+                            continue;
+                        };
+                        let transform = append_transform(transform, p.transform);
+                        let half_width = p.stroke.as_ref().map_or(0.0, |s| s.width.get() / 2.0);
+                        let (left, top) = (bbox.x() - half_width, bbox.y() - half_width);
+                        let (right, bottom) =
+                            (bbox.right() + half_width, bbox.bottom() + half_width);
+                        for (x, y) in [(left, top), (right, top), (right, bottom), (left, bottom)] {
+                            let (x, y) = transform.apply(x, y);
+                            bounds.extend_with(Pos2::new(x as _, y as _));
+                        }
+                    }
+                    usvg::NodeKind::Group(g) => collect(
+                        node.children(),
+                        append_transform(transform, g.transform),
+                        bounds,
+                    ),
+                    _ => {}
+                }
             }
-            Background::Custom {
-                fill,
-                rounding,
-                stroke,
-            } => ui.painter().rect(frame_rect, *rounding, *fill, *stroke),
         }
 
-        ui.painter().with_clip_rect(frame_rect).add(shape);
+        let (nodes, transform) = self.root_nodes();
+        let mut bounds = Rect::NOTHING;
+        collect(nodes.into_iter(), transform, &mut bounds);
+        (bounds.is_finite() && bounds.is_positive()).then_some(bounds)
+    }
+    /// resolve the bounds of [`Svg::with_trim`] after the tree or the element changed
+    fn update_trim(&mut self) {
+        self.trim_bounds = self.trim.then(|| self.content_bounds()).flatten();
+    }
+    /// the viewport set with [`Svg::with_viewport`] or [`Svg::with_element`],
+    /// or the content bounds of [`Svg::with_trim`]
+    fn viewport(&self) -> Option<Rect> {
+        self.trim_bounds.or(self.viewport)
+    }
+    /// top level nodes to draw and the absolute transform of their parent
+    fn root_nodes(&self) -> (Vec<usvg::Node>, usvg::Transform) {
+        use usvg::NodeExt;
+
+        let tree = &self.tree.1;
 
-        response
+        match &self.element {
+            Some(id) => match tree.node_by_id(id) {
+                Some(node) => {
+                    let transform = node
+                        .parent()
+                        .map_or(Default::default(), |p| p.abs_transform());
+                    (vec![node], transform)
+                }
+                None => (Vec::new(), Default::default()),
+            },
+            None => (tree.root.children().collect(), Default::default()),
+        }
+    }
+    /// whether the colors of the mesh are determined by the cache key,
+    /// which isn't the case for color maps without [`Svg::with_color_key`]
+    fn cacheable_colors(&self) -> bool {
+        self.color_func.is_none() || self.color_key.is_some()
+    }
+    /// the color map, resolving the currentColor placeholder first
+    fn resolved_color_func(&self, current_color: Color32) -> ColorFunc {
+        let color_func = self.color_func.clone();
+        let placeholder = self.tree.2;
+        std::sync::Arc::new(move |c: &ColorContext| match &color_func {
+            _ if Some(c.color) == placeholder => current_color,
+            Some(color_func) => color_func(c),
+            None => c.color,
+        })
     }
-    /// original viewbox of the svg shape
+    /// ids of all elements in the svg, in document order
+    pub fn ids(&self) -> Vec<String> {
+        use usvg::NodeExt;
+
+        let tree = &self.tree.1;
+
+        tree.root
+            .descendants()
+            .map(|node| node.id().to_string())
+            .filter(|id| !id.is_empty())
+            .collect()
+    }
+    /// color set with [`Svg::with_color_for_id`] for the element with `id`
+    fn id_color(&self, id: &str) -> Option<Color32> {
+        if id.is_empty() {
+            return None;
+        }
+        self.id_colors
+            .iter()
+            .find(|(other, _)| other == id)
+            .map(|(_, color)| *color)
+    }
+    /// original viewbox of the svg shape, or the viewport set with [`Svg::with_viewport`]
+    /// or [`Svg::with_trim`].
+    /// without a `viewBox` attribute it's the root element's `width` and `height`
+    ///
+    /// empty axes fall back to the root element's size, or `1.0` if that's empty too,
+    /// so the scale of the icon stays finite
+    #[doc(alias = "view_box")]
     pub fn svg_rect(&self) -> Rect {
-        #[cfg(not(feature = "cached"))]
-        let tree = &self.tree;
-        #[cfg(feature = "cached")]
         let tree = &self.tree.1;
 
-        to_egui_rect(tree.view_box.rect)
+        let rect = self
+            .viewport()
+            .unwrap_or_else(|| to_egui_rect(tree.view_box.rect));
+        let valid = |s: f32| s > 0.0 && s.is_finite();
+        let non_empty = |s: f32, fallback: f64| {
+            [s, fallback as f32]
+                .into_iter()
+                .find(|s| valid(*s))
+                .unwrap_or(1.0)
+        };
+        let min = if rect.min.is_finite() {
+            rect.min
+        } else {
+            Pos2::ZERO
+        };
+        Rect::from_min_size(
+            min,
+            Vec2::new(
+                non_empty(rect.width(), tree.size.width()),
+                non_empty(rect.height(), tree.size.height()),
+            ),
+        )
+    }
+}
+impl Widget for Svg {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = self.desired_size(ui.available_size());
+        self.show_sized(ui, size).response
+    }
+}
+
+/// response of a shown svg icon. derefs to the inner [`Response`]
+pub struct SvgResponse {
+    pub response: Response,
+    /// the allocated frame
+    pub frame_rect: Rect,
+    /// where the icon is drawn inside the frame, after fitting
+    pub icon_rect: Rect,
+    /// whether geometry was dropped because of [`Svg::with_vertex_budget`]
+    /// or [`Svg::with_path_budget`]
+    pub truncated: bool,
+}
+impl std::ops::Deref for SvgResponse {
+    type Target = Response;
+    fn deref(&self) -> &Self::Target {
+        &self.response
+    }
+}
+impl From<SvgResponse> for Response {
+    fn from(response: SvgResponse) -> Self {
+        response.response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// distinct vertex colors of the icon's mesh
+    fn colors(svg: Svg) -> Vec<Color32> {
+        let mut colors = svg
+            .to_mesh_sized([16.0, 16.0])
+            .vertices
+            .iter()
+            .map(|v| v.color)
+            .collect::<Vec<_>>();
+        colors.sort_by_key(|c| c.to_array());
+        colors.dedup();
+        colors
+    }
+
+    #[test]
+    fn current_color() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+            <!-- currentColor in a comment -->
+            <rect id="currentColor" width="8" height="8" fill="currentColor"/>
+        </svg>"##;
+        let svg = Svg::new_from_str(svg).with_current_color(Color32::GREEN);
+        assert_eq!(colors(svg.clone()), [Color32::GREEN]);
+        assert_eq!(svg.ids(), ["currentColor"]);
+    }
+
+    #[test]
+    fn current_color_uses_color_property() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+            <g color="red"><rect width="8" height="8" fill="currentColor"/></g>
+            <rect x="8" width="8" height="8" style="color: blue; fill: currentColor"/>
+        </svg>"##;
+        let svg = Svg::new_from_str(svg).with_current_color(Color32::GREEN);
+        assert_eq!(
+            colors(svg),
+            [Color32::from_rgb(0, 0, 255), Color32::from_rgb(255, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn current_color_keeps_placeholder_colors() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+            <rect width="8" height="8" fill="#010203"/>
+            <rect x="8" width="8" height="8" fill="currentColor"/>
+        </svg>"##;
+        let svg = Svg::new_from_str(svg).with_current_color(Color32::GREEN);
+        assert_eq!(
+            colors(svg),
+            [Color32::GREEN, Color32::from_rgb(0x01, 0x02, 0x03)]
+        );
+    }
+
+    #[test]
+    fn svgz_round_trip() {
+        use std::io::Write;
+
+        let data = include_bytes!("../examples/test.svg");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(data).unwrap();
+        let svgz = encoder.finish().unwrap();
+
+        let svg = Svg::new(data);
+        let decompressed = Svg::from_svgz(&svgz).unwrap();
+        assert_eq!(decompressed.size(), svg.size());
+        assert_eq!(
+            decompressed.stats(svg.size()).paths,
+            svg.stats(svg.size()).paths
+        );
+        assert!(svg.stats(svg.size()).paths > 0);
+        assert_eq!(
+            Svg::try_from_str(std::str::from_utf8(data).unwrap())
+                .unwrap()
+                .size(),
+            svg.size()
+        );
+        assert!(Svg::from_svgz(data).is_err());
+    }
+
+    #[test]
+    fn data_uri() {
+        let base64 = "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHZpZXdCb3g9IjAgMCAxNiA4Ij48cmVjdCB3aWR0aD0iOCIgaGVpZ2h0PSI4Ii8+PGNpcmNsZSBjeD0iMTIiIGN5PSI0IiByPSI0Ii8+PC9zdmc+";
+        let percent = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' \
+            viewBox='0 0 16 8'%3E%3Crect width='8' height='8'/%3E%3C/svg%3E";
+        let svg = Svg::new_from_str(base64);
+        assert_eq!(svg.size(), Vec2::new(16.0, 8.0));
+        assert_eq!(svg.stats([16.0, 8.0]).paths, 2);
+        let svg = Svg::new_from_str(percent);
+        assert_eq!(svg.stats([16.0, 8.0]).paths, 1);
+        assert!(matches!(
+            Svg::try_from_str("data:image/svg+xml;base64,!!!"),
+            Err(SvgError::DataUri)
+        ));
+    }
+
+    #[test]
+    fn invalid_svg() {
+        #[cfg(feature = "cached")]
+        let len = Svg::cache_len();
+        assert!(matches!(
+            Svg::try_new(b"<svg xmlns=\"http://www.w3.org/2000/svg\"><rect"),
+            Err(SvgError::Parse(_))
+        ));
+        assert!(matches!(
+            Svg::try_new(b"not a svg"),
+            Err(SvgError::Parse(_))
+        ));
+        assert!(Svg::try_from_str("<svg></svg>").is_err());
+        #[cfg(feature = "cached")]
+        {
+            assert_eq!(Svg::cache_len(), len);
+            Svg::new_from_str(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#);
+            assert_eq!(Svg::cache_len(), len + 1);
+        }
+    }
+
+    #[test]
+    fn split_high_detail_mesh() {
+        let circles = (0..400)
+            .map(|i| format!(r#"<circle cx="{}" cy="{}" r="0.4"/>"#, i % 20, i / 20))
+            .collect::<String>();
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 20">{circles}</svg>"#
+        );
+        let mesh = Svg::new_from_str(&svg).to_mesh_sized([512.0, 512.0]);
+        let max_vertices = 200;
+        assert!(mesh.vertices.len() > 4 * max_vertices);
+
+        let meshes = split_mesh(mesh.clone(), max_vertices);
+        assert!(meshes.len() > 4);
+        assert!(meshes.iter().all(|m| m.vertices.len() <= max_vertices));
+        assert!(meshes.iter().all(|m| m.is_valid()));
+        assert_eq!(
+            meshes.iter().map(|m| m.indices.len()).sum::<usize>(),
+            mesh.indices.len()
+        );
+    }
+
+    #[test]
+    fn justified_length_fallbacks() {
+        assert_eq!(justified_length(24.0, 14.0, 100.0), 24.0);
+        assert_eq!(justified_length(0.0, 14.0, 100.0), 14.0);
+        assert_eq!(justified_length(-3.0, 14.0, 100.0), 14.0);
+        assert_eq!(justified_length(f32::INFINITY, 14.0, 100.0), 100.0);
+        assert_eq!(justified_length(f32::NAN, 14.0, 100.0), 100.0);
+    }
+
+    #[test]
+    fn grayscale_before_tint() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+            <rect width="8" height="8" fill="#ff0000"/>
+        </svg>"##;
+        let svg = Svg::new_from_str(svg);
+        // luminance of pure red, 0.2126 * 255
+        assert_eq!(colors(svg.clone().grayscale()), [Color32::from_gray(54)]);
+        let tint = Color32::from_rgb(0, 128, 255);
+        assert_eq!(colors(svg.with_tint(tint).grayscale()), [tint]);
+    }
+
+    #[test]
+    fn empty_view_boxes() {
+        let rect = r#"<rect width="8" height="8"/>"#;
+        let mut svgs = ["0 0 16 0", "0 0 0 16", "0 0 0 0"]
+            .map(|view_box| {
+                Svg::new_from_str(&format!(
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{view_box}" width="16" height="16">{rect}</svg>"#
+                ))
+            })
+            .to_vec();
+        let svg = Svg::new_from_str(&format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">{rect}</svg>"#
+        ));
+        for viewport in [
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(16.0, 0.0)),
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(0.0, 16.0)),
+            Rect::from_min_size(Pos2::ZERO, Vec2::ZERO),
+            Rect::NOTHING,
+        ] {
+            svgs.push(svg.clone().with_viewport(viewport));
+        }
+
+        let ctx = Context::default();
+        for svg in svgs {
+            let svg_rect = svg.svg_rect();
+            assert!(svg_rect.is_finite() && svg_rect.is_positive());
+            assert!(svg_rect.aspect_ratio().is_finite());
+            assert_eq!(svg.stats([16.0, 16.0]).paths, 1);
+            assert_eq!(svg.stats([16.0, 0.0]), TessellationStats::default());
+
+            let _ = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let rect = svg.clone().show_justified(ui).response.rect;
+                    assert!(rect.is_finite());
+                    let rect = svg.clone().show_sized(ui, Vec2::ZERO).response.rect;
+                    assert!(rect.is_finite() && rect.area() == 0.0);
+                });
+            });
+        }
+    }
+
+    #[test]
+    fn offset_view_box() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-10 20 40 20"><rect x="-10" y="20" width="40" height="20"/></svg>"#,
+        );
+        let bounds = |fit_mode| {
+            let mesh = svg
+                .clone()
+                .with_fit_mode(fit_mode)
+                .to_mesh_sized([20.0, 20.0]);
+            Rect::from_points(&mesh.vertices.iter().map(|v| v.pos).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            bounds(FitMode::Contain(Default::default())),
+            Rect::from_min_max(Pos2::new(0.0, 5.0), Pos2::new(20.0, 15.0))
+        );
+        assert_eq!(
+            bounds(FitMode::Cover),
+            Rect::from_min_max(Pos2::new(-10.0, 0.0), Pos2::new(30.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn trim_toggles() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><rect x="4" y="4" width="8" height="8"/><rect id="b" x="14" y="14" width="6" height="6"/></svg>"#,
+        );
+        assert_eq!(
+            svg.clone().with_trim(true).svg_rect(),
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(20.0, 20.0))
+        );
+        assert_eq!(
+            svg.clone().with_trim(true).with_trim(false).svg_rect(),
+            svg.svg_rect()
+        );
+
+        let element = svg.clone().with_element("b");
+        assert_eq!(
+            svg.clone().with_trim(true).with_element("b").svg_rect(),
+            element.clone().with_trim(true).svg_rect()
+        );
+        assert_eq!(
+            element.clone().with_trim(true).with_trim(false).svg_rect(),
+            element.svg_rect()
+        );
+    }
+
+    #[test]
+    fn degenerate_tolerance() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><circle cx="8" cy="8" r="6" stroke="black"/></svg>"#,
+        );
+        let minimum = svg.clone().with_tolerance(1e-3).stats([64.0, 64.0]);
+        assert!(minimum.vertices > 0 && minimum.vertices < 100_000);
+        for tolerance in [0.0, -1.0, f32::NAN] {
+            let svg = svg.clone().with_tolerance(tolerance);
+            assert_eq!(svg.stats([64.0, 64.0]), minimum);
+            let _ = Context::default().run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    svg.show_sized(ui, Vec2::splat(64.0));
+                });
+            });
+        }
+    }
+
+    #[test]
+    fn hidden_paths() {
+        let svg = Svg::new(include_bytes!("../examples/test_visibility.svg"));
+        assert_eq!(
+            colors(svg.clone()),
+            [
+                Color32::from_rgb(0x2e, 0xc2, 0x7e),
+                Color32::from_rgb(0x35, 0x84, 0xe4)
+            ]
+        );
+        assert_eq!(svg.stats([64.0, 32.0]).paths, 2);
+    }
+
+    #[test]
+    fn lyon_failures() {
+        let degenerate = [
+            r#"<path d="M5 5 Z" stroke="black" stroke-linecap="round"/>"#,
+            r#"<path d="M5 5 L5 5" stroke="black" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            r#"<path d="M0 0 L0 0 L0 0 Z M1 1 L1 1 Z" stroke="black" stroke-linecap="square"/>"#,
+            r#"<path d="M0 0 C1e-30 0 0 1e-30 1e-30 1e-30 Z" stroke="black" stroke-linejoin="round"/>"#,
+            r#"<path d="M0 0 L1e30 1e30 L-1e30 1e30 Z" stroke="black"/>"#,
+            r#"<path d="M0 0 L3e38 0 L-3e38 3e38 Z" stroke="black" stroke-width="1e38"/>"#,
+            // overflows f32 once transformed, which used to trip an assertion in lyon
+            r#"<path d="M0 0 L1e38 0 L1e38 1e38 Z" transform="scale(100)" stroke="black"/>"#,
+            r#"<path d="M0 0 L1 0 L1 1 Z" transform="matrix(1e38 1e38 1e38 1e38 0 0)"/>"#,
+        ];
+        for path in degenerate {
+            let svg = Svg::new_from_str(&format!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+                    {path}<rect x="8" y="8" width="8" height="8" fill="#00ff00"/>
+                </svg>"##
+            ));
+            let mesh = svg.to_mesh_sized([16.0, 16.0]);
+            assert!(mesh.is_valid());
+            assert!(mesh.vertices.iter().all(|v| v.pos.is_finite()));
+            assert!(colors(svg).contains(&Color32::GREEN));
+        }
+    }
+
+    #[test]
+    fn fill_rule() {
+        let ring = |rule: &str| {
+            Svg::new_from_str(&format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 32 32">
+                    <path fill-rule="{rule}" d="M 16,2 A 14,14 0 1 0 16,30 A 14,14 0 1 0 16,2 Z M 16,9 A 7,7 0 1 0 16,23 A 7,7 0 1 0 16,9 Z"/>
+                </svg>"#
+            ))
+            .to_mesh_sized([32.0, 32.0])
+        };
+        // whether any triangle of the mesh contains `p`
+        let covers = |mesh: &Mesh, p: Pos2| {
+            mesh.indices.chunks_exact(3).any(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[t[i] as usize].pos);
+                let side = |a: Pos2, b: Pos2| (b - a).x * (p - a).y - (b - a).y * (p - a).x;
+                let sides = [side(a, b), side(b, c), side(c, a)];
+                sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+            })
+        };
+        let center = Pos2::new(16.0, 16.0);
+        let even_odd = ring("evenodd");
+        assert!(!covers(&even_odd, center));
+        assert!(covers(&even_odd, Pos2::new(16.0, 5.0)));
+        assert!(covers(&ring("nonzero"), center));
+    }
+
+    #[test]
+    fn auto_tolerance() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><circle cx="8" cy="8" r="8"/></svg>"#,
+        )
+        .with_scale_tolerance(false);
+        let auto = svg.clone().with_auto_tolerance(0.5);
+        assert!(auto.stats([16.0, 16.0]).vertices < auto.stats([512.0, 512.0]).vertices);
+        let fixed = svg.with_tolerance(0.5);
+        assert_eq!(
+            fixed.stats([16.0, 16.0]).vertices,
+            fixed.stats([512.0, 512.0]).vertices
+        );
+    }
+
+    #[test]
+    fn color_maps_without_key() {
+        let svg = Svg::new_from_str(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><rect width="8" height="8" fill="#ff0000"/></svg>"##,
+        );
+        let ctx = Context::default();
+        let output = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                for color in [Color32::GREEN, Color32::BLUE] {
+                    svg.clone()
+                        .with_color_map(move |_| color)
+                        .show_sized(ui, Vec2::splat(16.0));
+                }
+            });
+        });
+        let mut colors = output
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.1 {
+                Shape::Mesh(mesh) => Some(mesh.vertices.iter().map(|v| v.color)),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        colors.sort_by_key(|c| c.to_array());
+        colors.dedup();
+        assert_eq!(colors, [Color32::BLUE, Color32::GREEN]);
+    }
+
+    #[test]
+    fn paint_in_ui_uses_ui_visuals() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><rect width="8" height="8" fill="currentColor"/></svg>"#,
+        );
+        let ctx = Context::default();
+        let output = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.visuals_mut().hyperlink_color = Color32::RED;
+                ui.visuals_mut().override_text_color = Some(Color32::GREEN);
+                let rect = Rect::from_min_size(ui.min_rect().min, Vec2::splat(16.0));
+                svg.clone()
+                    .with_ui_color(|v| v.hyperlink_color)
+                    .paint_in_ui_at(ui, rect);
+                svg.paint_in_ui_at(ui, rect);
+            });
+        });
+        let colors = output
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.1 {
+                Shape::Mesh(mesh) => mesh.vertices.first().map(|v| v.color),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(colors, [Color32::RED, Color32::GREEN]);
+    }
+
+    #[test]
+    fn inline_icon_on_baseline() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><rect width="16" height="16"/></svg>"#,
+        );
+        let ctx = Context::default();
+        let mut icon_rect = None;
+        let output = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    icon_rect = Some(svg.show_inline(ui).icon_rect);
+                    ui.label("x");
+                });
+            });
+        });
+        let baseline = output
+            .shapes
+            .iter()
+            .find_map(|s| match &s.1 {
+                Shape::Text(text) => {
+                    // the bottom of an x is on the baseline
+                    let glyph = text.galley.rows[0].glyphs[0];
+                    Some(text.pos.y + glyph.pos.y + glyph.uv_rect.offset.y + glyph.uv_rect.size.y)
+                }
+                _ => None,
+            })
+            .unwrap();
+        let icon_rect = icon_rect.unwrap();
+        assert!((icon_rect.bottom() - baseline).abs() < 0.5);
+        assert!(icon_rect.height() > 0.0);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn images_in_document_order() {
+        use egui::util::cache::CacheTrait;
+
+        const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+        let svg = Svg::new_from_str(&format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+                <rect width="16" height="16" fill="#ff0000"/>
+                <image width="16" height="16" href="data:image/png;base64,{PNG}"/>
+                <rect x="4" y="4" width="8" height="8" fill="#00ff00"/>
+            </svg>"##
+        ));
+        let ctx = Context::default();
+        let textures = || ctx.memory_mut(|mem| mem.caches.cache::<TextureCache>().len());
+        // the viewport clips the triangles, which must keep the image between the rects
+        let viewport = Rect::from_min_max(Pos2::new(2.0, 2.0), Pos2::new(14.0, 14.0));
+        for svg in [svg.clone(), svg.with_viewport(viewport)] {
+            let output = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    svg.clone().show_sized(ui, Vec2::splat(16.0));
+                });
+            });
+            let layers = output
+                .shapes
+                .iter()
+                .filter_map(|s| match &s.1 {
+                    Shape::Mesh(mesh) => Some((
+                        mesh.texture_id == TextureId::default(),
+                        mesh.vertices.first().map(|v| v.color),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                layers,
+                [
+                    (true, Some(Color32::RED)),
+                    (false, Some(Color32::WHITE)),
+                    (true, Some(Color32::GREEN)),
+                ]
+            );
+        }
+        assert_eq!(textures(), 1);
+
+        // textures are dropped once the icon isn't shown anymore
+        for _ in 0..2 {
+            let _ = ctx.run(Default::default(), |_| {});
+        }
+        assert_eq!(textures(), 0);
+    }
+
+    #[test]
+    fn recent_rounded_sizes_stay_cached() {
+        use egui::util::cache::CacheTrait;
+
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><circle cx="8" cy="8" r="8"/></svg>"#,
+        );
+        for (svg, kept) in [(svg.clone(), 0), (svg.clone().with_size_rounding(8.0), 2)] {
+            let ctx = Context::default();
+            let meshes =
+                || ctx.memory_mut(|mem| mem.caches.cache::<tessellation::MeshCache>().len());
+            for size in [16.0, 17.0, 24.0, 25.0] {
+                let _ = ctx.run(Default::default(), |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        svg.clone().show_sized(ui, Vec2::splat(size));
+                    });
+                });
+            }
+            for _ in 0..2 {
+                let _ = ctx.run(Default::default(), |_| {});
+            }
+            assert_eq!(meshes(), kept);
+        }
+
+        // bounded to the most recent sizes
+        let svg = svg.with_size_rounding(1.0);
+        let ctx = Context::default();
+        for size in 1..=40 {
+            let _ = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    svg.clone().show_sized(ui, Vec2::splat(size as f32));
+                });
+            });
+        }
+        let _ = ctx.run(Default::default(), |_| {});
+        assert_eq!(
+            ctx.memory_mut(|mem| mem.caches.cache::<tessellation::MeshCache>().len()),
+            32
+        );
+    }
+
+    #[test]
+    fn tree_keys() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"/>"#;
+        let copy = svg.to_owned();
+        let (a, b) = (
+            Svg::new_with_key(svg.as_bytes(), 1),
+            Svg::new_with_key(copy.as_bytes(), 1),
+        );
+        assert_eq!(a.tree.0, b.tree.0);
+        #[cfg(feature = "cached")]
+        assert!(std::rc::Rc::ptr_eq(&a.tree.1, &b.tree.1));
+        assert_ne!(a.tree.0, Svg::new_with_key(svg.as_bytes(), 2).tree.0);
+
+        // sub slices starting at the same address are different svgs
+        static DATA: &[u8] =
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1\" height=\"1\"/>  ";
+        assert_ne!(
+            Svg::new(DATA).tree.0,
+            Svg::new(&DATA[..DATA.len() - 1]).tree.0
+        );
+    }
+
+    #[cfg(feature = "culled")]
+    #[test]
+    fn culling() {
+        let svg = Svg::new_from_str(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16"><rect width="16" height="16"/></svg>"#,
+        );
+        let painted = |svg: Svg, frame_rect: Rect, clip_rect: Rect| {
+            let ctx = Context::default();
+            let output = ctx.run(Default::default(), |ctx| {
+                let painter = ctx
+                    .layer_painter(LayerId::background())
+                    .with_clip_rect(clip_rect);
+                svg.paint_at(&painter, frame_rect);
+            });
+            output
+                .shapes
+                .iter()
+                .any(|s| matches!(&s.1, Shape::Mesh(m) if !m.is_empty()))
+        };
+
+        // the first tile is out of view, the others aren't
+        let tiled = svg.clone().with_fit_mode(FitMode::Tile {
+            tile_size: Vec2::splat(16.0),
+            spacing: Vec2::ZERO,
+        });
+        let frame_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(64.0));
+        let clip_rect = Rect::from_min_size(Pos2::new(32.0, 32.0), Vec2::splat(16.0));
+        assert!(painted(tiled, frame_rect, clip_rect));
+
+        // the icon is centered in the frame, and its rotated corner reaches into view
+        let svg = svg.with_fit_mode(FitMode::Size(Vec2::splat(16.0)));
+        let frame_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(32.0));
+        let clip_rect = Rect::from_min_max(Pos2::new(25.0, 14.0), Pos2::new(27.0, 18.0));
+        assert!(!painted(svg.clone(), frame_rect, clip_rect));
+        assert!(painted(
+            svg.with_rotation(std::f32::consts::FRAC_PI_4),
+            frame_rect,
+            clip_rect
+        ));
     }
 }
@@ -0,0 +1,141 @@
+use crate::tessellation::{paint_color, PathConvIter};
+use crate::*;
+use lyon::path::iterator::PathIterator;
+use lyon::path::PathEvent;
+
+/// a luminance mask sampled per vertex. only filled shapes are considered and
+/// shape edges inside a triangle are not resolved, so hard masks look soft
+pub struct Mask {
+    rect: Option<Rect>,
+    shapes: Vec<MaskShape>,
+}
+struct MaskShape {
+    polygons: Vec<Vec<Pos2>>,
+    even_odd: bool,
+    paint: usvg::Paint,
+    opacity: f64,
+    paint_transform: usvg::Transform,
+}
+impl Mask {
+    /// `None` if the mask can't be resolved, e.g. when relative to the bounding box
+    pub fn new(mask: &usvg::Mask, transform: usvg::Transform, tolerance: f32) -> Option<Self> {
+        if mask.content_units == usvg::Units::ObjectBoundingBox {
+            return None;
+        }
+        let rect = match mask.units {
+            usvg::Units::UserSpaceOnUse => {
+                let rect = to_egui_rect(mask.rect);
+                let points = [
+                    rect.left_top(),
+                    rect.right_top(),
+                    rect.right_bottom(),
+                    rect.left_bottom(),
+                ]
+                .map(|p| {
+                    let (x, y) = transform.apply(p.x as _, p.y as _);
+                    Pos2::new(x as _, y as _)
+                });
+                Some(Rect::from_points(&points))
+            }
+            usvg::Units::ObjectBoundingBox => None,
+        };
+
+        let mut shapes = Vec::new();
+        collect_shapes(&mask.root, transform, tolerance, &mut shapes);
+        Some(Mask { rect, shapes })
+    }
+    /// how much of a vertex at `pos` is visible, from 0 to 1
+    pub fn coverage_at(&self, pos: Pos2) -> f32 {
+        if self.rect.is_some_and(|rect| !rect.contains(pos)) {
+            return 0.0;
+        }
+
+        // composite the mask's shapes from bottom to top
+        let mut color = egui::Rgba::TRANSPARENT;
+        for shape in &self.shapes {
+            if !shape.contains(pos) {
+                continue;
+            }
+            let src = egui::Rgba::from(paint_color(
+                &shape.paint,
+                shape.opacity,
+                shape.paint_transform,
+                None,
+                None,
+                pos,
+            ));
+            color = src + color * (1.0 - src.a());
+        }
+
+        // the color is premultiplied, so the luminance already includes the alpha
+        0.2126 * color.r() + 0.7152 * color.g() + 0.0722 * color.b()
+    }
+}
+impl MaskShape {
+    fn contains(&self, pos: Pos2) -> bool {
+        let mut winding = 0;
+        for polygon in &self.polygons {
+            for i in 0..polygon.len() {
+                let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                let side = (b - a).x * (pos - a).y - (b - a).y * (pos - a).x;
+                if a.y <= pos.y && b.y > pos.y && side > 0.0 {
+                    winding += 1;
+                } else if a.y > pos.y && b.y <= pos.y && side < 0.0 {
+                    winding -= 1;
+                }
+            }
+        }
+        if self.even_odd {
+            winding % 2 != 0
+        } else {
+            winding != 0
+        }
+    }
+}
+fn collect_shapes(
+    parent: &usvg::Node,
+    transform: usvg::Transform,
+    tolerance: f32,
+    shapes: &mut Vec<MaskShape>,
+) {
+    for node in parent.children() {
+        match &*node.borrow() {
+            usvg::NodeKind::Path(p) if p.visibility != usvg::Visibility::Visible => {}
+            usvg::NodeKind::Path(p) => {
+                let Some(fill) = &p.fill else {
+                    continue;
+                };
+                let transform = append_transform(transform, p.transform);
+                let mut polygons = Vec::new();
+                for event in PathConvIter::new(p).flattened(tolerance) {
+                    let point = match event {
+                        PathEvent::Begin { at } => {
+                            polygons.push(Vec::new());
+                            at
+                        }
+                        PathEvent::Line { to, .. } => to,
+                        _ => continue,
+                    };
+                    let (x, y) = transform.apply(point.x as _, point.y as _);
+                    if let Some(polygon) = polygons.last_mut() {
+                        polygon.push(Pos2::new(x as _, y as _));
+                    }
+                }
+                shapes.push(MaskShape {
+                    polygons,
+                    even_odd: fill.rule == usvg::FillRule::EvenOdd,
+                    paint: fill.paint.clone(),
+                    opacity: fill.opacity.get(),
+                    paint_transform: paint_transform(&fill.paint, transform, p.data.bbox()),
+                });
+            }
+            usvg::NodeKind::Group(g) => collect_shapes(
+                &node,
+                append_transform(transform, g.transform),
+                tolerance,
+                shapes,
+            ),
+            usvg::NodeKind::Image(_) | usvg::NodeKind::Text(_) => {}
+        }
+    }
+}
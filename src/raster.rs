@@ -0,0 +1,192 @@
+use crate::*;
+
+/// textured meshes of the embedded raster images in document order,
+/// `None` for images that aren't drawn
+pub fn image_meshes(svg: &Svg, ctx: &Context, rect: Rect, scale: Vec2) -> Vec<Option<Mesh>> {
+    #[cfg(feature = "puffin")]
+    puffin::profile_function!();
+
+    let (nodes, transform) = svg.root_nodes();
+    let mut meshes = Vec::new();
+    collect_recursive(
+        svg,
+        ctx,
+        rect,
+        scale,
+        nodes.into_iter(),
+        transform,
+        &mut meshes,
+    );
+    meshes
+}
+fn collect_recursive(
+    svg: &Svg,
+    ctx: &Context,
+    rect: Rect,
+    scale: Vec2,
+    nodes: impl Iterator<Item = usvg::Node>,
+    parent_transform: usvg::Transform,
+    meshes: &mut Vec<Option<Mesh>>,
+) {
+    for node in nodes {
+        match &*node.borrow() {
+            usvg::NodeKind::Image(image) => {
+                let data = match &image.kind {
+                    usvg::ImageKind::JPEG(data)
+                    | usvg::ImageKind::PNG(data)
+                    | usvg::ImageKind::GIF(data) => Some(data),
+                    usvg::ImageKind::SVG(_) => None,
+                };
+                let texture = data
+                    .filter(|_| image.visibility == usvg::Visibility::Visible)
+                    .and_then(|data| load_texture(ctx, data));
+                let Some(texture) = texture else {
+                    meshes.push(None);
+                    continue;
+                };
+
+                let (pos_rect, uv_rect) = fit_image(image.view_box, texture.size_vec2());
+                let transform = append_transform(parent_transform, image.transform);
+                let to_egui_pos = |svg_pos: Pos2| -> Pos2 {
+                    let (x, y) = transform.apply(svg_pos.x as _, svg_pos.y as _);
+                    let mut pos = Pos2::new(x as _, y as _);
+                    pos -= svg.svg_rect().min.to_vec2();
+                    pos.x *= scale.x;
+                    pos.y *= scale.y;
+                    pos += rect.min.to_vec2();
+                    pos
+                };
+
+                let mut mesh = Mesh::with_texture(texture.id());
+                for (pos, uv) in [
+                    (pos_rect.left_top(), uv_rect.left_top()),
+                    (pos_rect.right_top(), uv_rect.right_top()),
+                    (pos_rect.right_bottom(), uv_rect.right_bottom()),
+                    (pos_rect.left_bottom(), uv_rect.left_bottom()),
+                ] {
+                    mesh.vertices.push(epaint::Vertex {
+                        pos: to_egui_pos(pos),
+                        uv,
+                        color: Color32::WHITE,
+                    });
+                }
+                mesh.indices.extend_from_slice(&[0, 1, 2, 0, 2, 3]);
+                meshes.push(Some(mesh));
+            }
+            usvg::NodeKind::Group(g) => collect_recursive(
+                svg,
+                ctx,
+                rect,
+                scale,
+                node.children(),
+                append_transform(parent_transform, g.transform),
+                meshes,
+            ),
+            usvg::NodeKind::Path(_) | usvg::NodeKind::Text(_) => {}
+        }
+    }
+}
+
+/// split `mesh` at the index positions in `breaks` and put the image of each break in between,
+/// in paint order. images without a break, e.g. after a truncated budget, are painted on top
+pub fn interleave_images(
+    mesh: Mesh,
+    breaks: &[usize],
+    images: impl IntoIterator<Item = Option<Mesh>>,
+) -> Vec<Mesh> {
+    let mut images = images.into_iter();
+    let mut layers = Vec::new();
+    let mut start = 0;
+    for (&end, image) in breaks.iter().zip(images.by_ref()) {
+        let Some(image) = image else {
+            continue;
+        };
+        if end > start {
+            layers.push(sub_mesh(&mesh, start..end));
+            start = end;
+        }
+        layers.push(image);
+    }
+    if start == 0 {
+        layers.push(mesh);
+    } else if start < mesh.indices.len() {
+        layers.push(sub_mesh(&mesh, start..mesh.indices.len()));
+    }
+    layers.extend(images.flatten());
+    layers
+}
+/// the triangles of `mesh` in the index `range`, with only the vertices they use
+fn sub_mesh(mesh: &Mesh, range: std::ops::Range<usize>) -> Mesh {
+    let mut sub = Mesh::with_texture(mesh.texture_id);
+    let mut remap = std::collections::HashMap::new();
+    for &i in &mesh.indices[range] {
+        let index = *remap.entry(i).or_insert_with(|| {
+            sub.vertices.push(mesh.vertices[i as usize]);
+            sub.vertices.len() as u32 - 1
+        });
+        sub.indices.push(index);
+    }
+    sub
+}
+
+/// position and uv rect of an image placed into its view box according to `preserveAspectRatio`
+fn fit_image(view_box: usvg::ViewBox, image_size: Vec2) -> (Rect, Rect) {
+    let view_rect = to_egui_rect(view_box.rect);
+    let full_uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+    if view_box.aspect.align == usvg::Align::None || image_size.min_elem() <= 0.0 {
+        return (view_rect, full_uv);
+    }
+
+    let factor = view_rect.size() / image_size;
+    let factor = if view_box.aspect.slice {
+        factor.max_elem()
+    } else {
+        factor.min_elem()
+    };
+    let align = match view_box.aspect.align {
+        usvg::Align::None | usvg::Align::XMidYMid => Align2::CENTER_CENTER,
+        usvg::Align::XMinYMin => Align2::LEFT_TOP,
+        usvg::Align::XMidYMin => Align2::CENTER_TOP,
+        usvg::Align::XMaxYMin => Align2::RIGHT_TOP,
+        usvg::Align::XMinYMid => Align2::LEFT_CENTER,
+        usvg::Align::XMaxYMid => Align2::RIGHT_CENTER,
+        usvg::Align::XMinYMax => Align2::LEFT_BOTTOM,
+        usvg::Align::XMidYMax => Align2::CENTER_BOTTOM,
+        usvg::Align::XMaxYMax => Align2::RIGHT_BOTTOM,
+    };
+    let image_rect = align.align_size_within_rect(image_size * factor, view_rect);
+
+    // with `slice` the image overflows the view box, so crop it through the uvs
+    let pos_rect = image_rect.intersect(view_rect);
+    let uv_rect = Rect::from_min_max(
+        ((pos_rect.min - image_rect.min) / image_rect.size()).to_pos2(),
+        ((pos_rect.max - image_rect.min) / image_rect.size()).to_pos2(),
+    );
+    (pos_rect, uv_rect)
+}
+
+/// decoded textures are cached in egui's memory while they're used, keyed on the image's content
+fn load_texture(ctx: &Context, data: &[u8]) -> Option<TextureHandle> {
+    use egui::epaint::ahash::RandomState;
+
+    let key = RandomState::with_seed(0).hash_one(data);
+    if let Some(texture) = ctx.memory_mut(|mem| mem.caches.cache::<TextureCache>().get(key)) {
+        return Some(texture);
+    }
+
+    let image = ::image::load_from_memory(data).ok()?.to_rgba8();
+    let texture = ctx.load_texture(
+        format!("egui_svgicon::raster::{key:016x}"),
+        ColorImage::from_rgba_unmultiplied(
+            [image.width() as _, image.height() as _],
+            image.as_raw(),
+        ),
+        TextureOptions::LINEAR,
+    );
+    ctx.memory_mut(|mem| {
+        mem.caches
+            .cache::<TextureCache>()
+            .insert(key, texture.clone())
+    });
+    Some(texture)
+}
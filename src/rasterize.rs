@@ -0,0 +1,187 @@
+use crate::*;
+use resvg::tiny_skia;
+
+/// render the icon with resvg into a texture and return a quad covering `rect`
+pub fn raster_mesh(svg: &Svg, ctx: &Context, rect: Rect, current_color: Color32) -> Mesh {
+    #[cfg(feature = "puffin")]
+    puffin::profile_function!();
+
+    let max_side = ctx.input(|i| i.max_texture_side) as f32;
+    let size = (rect.size() * ctx.pixels_per_point())
+        .round()
+        .clamp(Vec2::splat(1.0), Vec2::splat(max_side));
+    let key = texture_key(svg, size, current_color);
+
+    // color maps without a key are rendered every frame, as the key can't tell them apart
+    let cacheable = svg.cacheable_colors();
+    let cached = cacheable
+        .then(|| ctx.memory_mut(|mem| mem.caches.cache::<TextureCache>().get(key)))
+        .flatten();
+    let texture = match cached {
+        Some(texture) => Some(texture),
+        None => render(svg, size, current_color).map(|image| {
+            let texture = ctx.load_texture(
+                format!("egui_svgicon::rasterize::{key:016x}"),
+                image,
+                TextureOptions::LINEAR,
+            );
+            if cacheable {
+                ctx.memory_mut(|mem| {
+                    mem.caches
+                        .cache::<TextureCache>()
+                        .insert(key, texture.clone())
+                });
+            }
+            texture
+        }),
+    };
+
+    let mut mesh = Mesh::default();
+    if let Some(texture) = texture {
+        mesh.texture_id = texture.id();
+        mesh.add_rect_with_uv(
+            rect,
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+    }
+    mesh
+}
+
+fn texture_key(svg: &Svg, size: Vec2, current_color: Color32) -> u64 {
+    use egui::epaint::ahash::RandomState;
+    use std::hash::*;
+
+    let mut hasher = RandomState::with_seed(0).build_hasher();
+    svg.tree.0.hash(&mut hasher);
+    let svg_rect = svg.svg_rect();
+    [svg_rect.min, svg_rect.max]
+        .map(|p| [p.x.to_bits(), p.y.to_bits()])
+        .hash(&mut hasher);
+    svg.element.hash(&mut hasher);
+    svg.color_key.hash(&mut hasher);
+    svg.id_colors.hash(&mut hasher);
+    svg.supersample.hash(&mut hasher);
+    [size.x.to_bits(), size.y.to_bits()].hash(&mut hasher);
+    current_color.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// render the visible nodes stretched from the svg rect to `size` pixels
+fn render(svg: &Svg, size: Vec2, current_color: Color32) -> Option<ColorImage> {
+    let factor = svg.supersample.max(1);
+    let image_size = [size.x as usize, size.y as usize];
+    let size = size * factor as f32;
+    let svg_rect = svg.svg_rect();
+    let (nodes, transform) = svg.root_nodes();
+    let root = usvg::Node::new(usvg::NodeKind::Group(usvg::Group {
+        transform,
+        ..Default::default()
+    }));
+    for node in nodes {
+        root.append(node.make_deep_copy());
+    }
+    map_colors(svg, &root, &svg.resolved_color_func(current_color));
+
+    let tree = usvg::Tree {
+        size: usvg::Size::new(size.x as _, size.y as _)?,
+        view_box: usvg::ViewBox {
+            rect: usvg::Rect::new(
+                svg_rect.min.x as _,
+                svg_rect.min.y as _,
+                svg_rect.width() as _,
+                svg_rect.height() as _,
+            )?,
+            aspect: usvg::AspectRatio {
+                defer: false,
+                align: usvg::Align::None,
+                slice: false,
+            },
+        },
+        root,
+    };
+
+    let mut pixmap = tiny_skia::Pixmap::new(size.x as _, size.y as _)?;
+    resvg::render(
+        &tree,
+        usvg::FitTo::Original,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )?;
+
+    if factor == 1 {
+        return Some(ColorImage {
+            size: [pixmap.width() as _, pixmap.height() as _],
+            pixels: pixmap
+                .pixels()
+                .iter()
+                .map(|p| Color32::from_rgba_premultiplied(p.red(), p.green(), p.blue(), p.alpha()))
+                .collect(),
+        });
+    }
+
+    // average each `factor` x `factor` block, which is exact for premultiplied colors
+    let (factor, width) = (factor as usize, pixmap.width() as usize);
+    let pixels = pixmap.pixels();
+    let mut image = ColorImage::new(image_size, Color32::TRANSPARENT);
+    for y in 0..image_size[1] {
+        for x in 0..image_size[0] {
+            let mut sum = [0u32; 4];
+            for sy in 0..factor {
+                for sx in 0..factor {
+                    let p = pixels[(y * factor + sy) * width + x * factor + sx];
+                    for (sum, c) in sum
+                        .iter_mut()
+                        .zip([p.red(), p.green(), p.blue(), p.alpha()])
+                    {
+                        *sum += c as u32;
+                    }
+                }
+            }
+            let count = (factor * factor) as u32;
+            let [r, g, b, a] = sum.map(|c| ((c + count / 2) / count) as u8);
+            image[(x, y)] = Color32::from_rgba_premultiplied(r, g, b, a);
+        }
+    }
+    Some(image)
+}
+
+/// apply the color map to solid fills and strokes. gradient stops are shared, so they're kept
+fn map_colors(svg: &Svg, root: &usvg::Node, color_func: &ColorFunc) {
+    use usvg::NodeExt;
+
+    for node in root.descendants() {
+        let id_color = node.ancestors().find_map(|n| svg.id_color(&n.id()));
+        let mut node = node.borrow_mut();
+        let usvg::NodeKind::Path(path) = &mut *node else {
+            continue;
+        };
+        let usvg::Path {
+            id, fill, stroke, ..
+        } = path;
+        let paints = [
+            fill.as_mut()
+                .map(|f| (&mut f.paint, &mut f.opacity, PaintKind::Fill)),
+            stroke
+                .as_mut()
+                .map(|s| (&mut s.paint, &mut s.opacity, PaintKind::Stroke)),
+        ];
+        for (paint, opacity, kind) in paints.into_iter().flatten() {
+            let usvg::Paint::Color(color) = paint else {
+                continue;
+            };
+            let [r, g, b, a] = id_color
+                .unwrap_or_else(|| {
+                    color_func(&ColorContext {
+                        color: Color32::from_rgb(color.red, color.green, color.blue),
+                        kind,
+                        id: id.as_str(),
+                        opacity: opacity.get() as f32,
+                    })
+                })
+                .to_srgba_unmultiplied();
+            *color = usvg::Color::new_rgb(r, g, b);
+            *opacity = usvg::Opacity::new_clamped(opacity.get() * a as f64 / 255.0);
+        }
+    }
+}
@@ -2,128 +2,982 @@ use crate::*;
 use lyon::lyon_tessellation::geometry_builder::*;
 use lyon::lyon_tessellation::*;
 use lyon::math::Point;
+use lyon::path::iterator::PathIterator;
 use lyon::path::PathEvent;
 
-pub fn tessellate(svg: &Svg, rect: Rect, scale: Vec2) -> Mesh {
+/// smallest tolerance passed to lyon, see [`Svg::with_tolerance`]
+const MIN_TOLERANCE: f32 = 1e-3;
+
+/// number of drawn paths from which the `rayon` feature tessellates them in parallel
+#[cfg(feature = "rayon")]
+const PARALLEL_PATHS: usize = 16;
+
+/// limits set with [`Svg::with_vertex_budget`] and [`Svg::with_path_budget`]
+struct Budget {
+    vertices: usize,
+    paths: usize,
+    truncated: bool,
+    /// number of paths added to the mesh
+    tessellated: usize,
+}
+
+/// a tessellated icon
+pub struct Tessellation {
+    pub mesh: Mesh,
+    pub stats: TessellationStats,
+    /// number of indices in the mesh before each embedded image, in document order,
+    /// so images can be painted between the shapes around them
+    #[cfg_attr(not(feature = "image"), allow(dead_code))]
+    pub image_breaks: Vec<usize>,
+}
+
+/// a tessellation, possibly shared with the [`MeshCache`], and where to paint it
+pub struct PlacedTessellation {
+    pub tessellation: std::sync::Arc<Tessellation>,
+    /// scale from the tessellated size to the painted one
+    pub factor: Vec2,
+    /// translation to the painted rect
+    pub offset: Vec2,
+}
+impl From<Tessellation> for PlacedTessellation {
+    fn from(tessellation: Tessellation) -> Self {
+        Self {
+            tessellation: std::sync::Arc::new(tessellation),
+            factor: Vec2::splat(1.0),
+            offset: Vec2::ZERO,
+        }
+    }
+}
+impl PlacedTessellation {
+    /// the mesh to paint. shapes own their buffers, so a mesh shared with the cache is copied
+    /// once here, scaling and translating each vertex while copying. otherwise it's moved out
+    pub fn take_mesh(&mut self) -> Mesh {
+        let (factor, offset) = (self.factor, self.offset);
+        let place = move |v: &epaint::Vertex| epaint::Vertex {
+            pos: (v.pos.to_vec2() * factor + offset).to_pos2(),
+            ..*v
+        };
+        match std::sync::Arc::get_mut(&mut self.tessellation) {
+            Some(tessellation) => {
+                let mut mesh = std::mem::take(&mut tessellation.mesh);
+                if factor != Vec2::splat(1.0) || offset != Vec2::ZERO {
+                    mesh.vertices.iter_mut().for_each(|v| *v = place(v));
+                }
+                mesh
+            }
+            None => {
+                let mesh = &self.tessellation.mesh;
+                Mesh {
+                    indices: mesh.indices.clone(),
+                    vertices: mesh.vertices.iter().map(place).collect(),
+                    texture_id: mesh.texture_id,
+                }
+            }
+        }
+    }
+}
+
+/// number of unused meshes of rounded sizes kept in the [`MeshCache`]
+const RECENT_ROUNDED_MESHES: usize = 32;
+
+/// tessellated meshes in egui's memory. like egui's `FrameCache`, meshes that weren't used since
+/// the last update are dropped, except for the most recently used ones of rounded sizes,
+/// see [`Svg::with_size_rounding`]. a resize animation that goes back and forth between size
+/// buckets reuses their meshes that way
+#[derive(Default)]
+pub struct MeshCache {
+    generation: u32,
+    meshes: std::collections::HashMap<u64, CachedMesh>,
+}
+struct CachedMesh {
+    tessellation: std::sync::Arc<Tessellation>,
+    used: u32,
+    rounded: bool,
+}
+impl MeshCache {
+    /// meshes are shared so a cache hit doesn't copy them while holding the memory lock
+    pub fn get_or_insert_with(
+        &mut self,
+        key: u64,
+        rounded: bool,
+        tessellate: impl FnOnce() -> Tessellation,
+    ) -> std::sync::Arc<Tessellation> {
+        let generation = self.generation;
+        let cached = self.meshes.entry(key).or_insert_with(|| CachedMesh {
+            tessellation: std::sync::Arc::new(tessellate()),
+            used: generation,
+            rounded,
+        });
+        cached.used = generation;
+        cached.tessellation.clone()
+    }
+}
+impl egui::util::cache::CacheTrait for MeshCache {
+    fn update(&mut self) {
+        let generation = self.generation;
+        let mut recent = self
+            .meshes
+            .iter()
+            .filter(|(_, m)| m.rounded && m.used != generation)
+            .map(|(key, m)| (generation.wrapping_sub(m.used), *key))
+            .collect::<Vec<_>>();
+        recent.sort_unstable();
+        recent.truncate(RECENT_ROUNDED_MESHES);
+        self.meshes
+            .retain(|key, m| m.used == generation || recent.iter().any(|(_, k)| k == key));
+        self.generation = generation.wrapping_add(1);
+    }
+    fn len(&self) -> usize {
+        self.meshes.len()
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// state shared while tessellating the nodes of one icon
+struct TessellateContext<'a> {
+    svg: &'a Svg,
+    scale: Vec2,
+    rect: Rect,
+    color_func: &'a ColorFunc,
+    buffer: &'a mut VertexBuffers<epaint::Vertex, u32>,
+    fill_tessellator: &'a mut FillTessellator,
+    stroke_tessellator: &'a mut StrokeTessellator,
+    /// scratch geometry of the current path
+    geometry: &'a mut PathGeometry,
+    /// geometry tessellated up front, see [`tessellate_parallel`]
+    #[cfg(feature = "rayon")]
+    parallel: std::collections::HashMap<usize, Option<PathGeometry>>,
+    budget: Budget,
+    image_breaks: Vec<usize>,
+}
+
+pub fn tessellate(svg: &Svg, rect: Rect, scale: Vec2, current_color: Color32) -> Tessellation {
+    tessellate_with(svg, rect, scale, current_color, cfg!(feature = "rayon"))
+}
+#[cfg_attr(not(feature = "rayon"), allow(unused_variables))]
+fn tessellate_with(
+    svg: &Svg,
+    rect: Rect,
+    scale: Vec2,
+    current_color: Color32,
+    parallel: bool,
+) -> Tessellation {
     #[cfg(feature = "puffin")]
     puffin::profile_function!();
 
-    #[cfg(not(feature = "cached"))]
-    let tree = &svg.tree;
-    #[cfg(feature = "cached")]
-    let tree = &svg.tree.1;
+    thread_local! {
+        // tessellators keep their allocations between paths, so reuse them between icons too
+        static TESSELLATORS: std::cell::RefCell<(
+            FillTessellator,
+            StrokeTessellator,
+            PathGeometry,
+        )> = std::cell::RefCell::new((
+            FillTessellator::new(),
+            StrokeTessellator::new(),
+            PathGeometry::default(),
+        ));
+        // scratch buffer that only grows, so tessellating doesn't reallocate while pushing
+        static BUFFER: std::cell::RefCell<VertexBuffers<epaint::Vertex, u32>> =
+            std::cell::RefCell::new(VertexBuffers::new());
+    }
 
-    let mut buffer = VertexBuffers::<_, u32>::new();
-    tessellate_recursive(
-        svg,
-        scale,
-        rect,
-        &mut buffer,
-        &mut FillTessellator::new(),
-        &mut StrokeTessellator::new(),
-        &tree.root,
-        Default::default(),
-    );
+    let color_func = svg.resolved_color_func(current_color);
+    let (nodes, transform) = svg.root_nodes();
+    #[cfg(feature = "rayon")]
+    let parallel = if parallel {
+        tessellate_parallel(svg, scale, rect, &nodes, transform)
+    } else {
+        Default::default()
+    };
+    BUFFER.with(|buffer| {
+        let buffer = &mut *buffer.borrow_mut();
+        buffer.vertices.clear();
+        buffer.indices.clear();
+
+        let (budget, mut image_breaks) = TESSELLATORS.with(|tessellators| {
+            let (fill_tessellator, stroke_tessellator, geometry) = &mut *tessellators.borrow_mut();
+            let mut ctx = TessellateContext {
+                svg,
+                scale,
+                rect,
+                color_func: &color_func,
+                buffer,
+                fill_tessellator,
+                stroke_tessellator,
+                geometry,
+                #[cfg(feature = "rayon")]
+                parallel,
+                budget: Budget {
+                    vertices: svg.vertex_budget,
+                    paths: svg.path_budget,
+                    truncated: false,
+                    tessellated: 0,
+                },
+                image_breaks: Vec::new(),
+            };
+            tessellate_recursive(&mut ctx, nodes.into_iter(), transform, 1.0, None);
+            (ctx.budget, ctx.image_breaks)
+        });
+        if svg.viewport().is_some() {
+            let polygon = [
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ];
+            clip::clip_triangles(buffer, 0, &polygon, &mut image_breaks);
+        }
 
-    let mut mesh = Mesh::default();
-    std::mem::swap(&mut buffer.vertices, &mut mesh.vertices);
-    std::mem::swap(&mut buffer.indices, &mut mesh.indices);
-    mesh
+        // copy out with exact capacity, keeping the scratch allocation for the next icon
+        let mesh = Mesh {
+            vertices: buffer.vertices.clone(),
+            indices: buffer.indices.clone(),
+            ..Default::default()
+        };
+        let stats = TessellationStats {
+            vertices: mesh.vertices.len(),
+            indices: mesh.indices.len(),
+            paths: budget.tessellated,
+            truncated: budget.truncated,
+        };
+        Tessellation {
+            mesh,
+            stats,
+            image_breaks,
+        }
+    })
 }
 fn tessellate_recursive(
-    svg: &Svg,
-    scale: Vec2,
-    rect: Rect,
-    buffer: &mut VertexBuffers<epaint::Vertex, u32>,
-    fill_tesselator: &mut FillTessellator,
-    stroke_tesselator: &mut StrokeTessellator,
-    parent: &usvg::Node,
+    ctx: &mut TessellateContext,
+    nodes: impl Iterator<Item = usvg::Node>,
     parent_transform: usvg::Transform,
+    parent_opacity: f64,
+    parent_id_color: Option<Color32>,
 ) {
-    for node in parent.children() {
+    let (svg, scale, rect, color_func) = (ctx.svg, ctx.scale, ctx.rect, ctx.color_func);
+    let to_egui_pos = |svg_pos: Pos2| to_egui_pos(svg, scale, rect, svg_pos);
+    let to_svg_pos =
+        |pos: Pos2| -> Pos2 { ((pos - rect.min) / scale + svg.svg_rect().min.to_vec2()).to_pos2() };
+    let tolerance = lyon_tolerance(svg, scale);
+    for node in nodes {
+        if ctx.budget.truncated {
+            return;
+        }
+        let id_color = {
+            use usvg::NodeExt;
+            svg.id_color(&node.id()).or(parent_id_color)
+        };
         match &*node.borrow() {
+            // `display: none` is already removed by usvg
+            usvg::NodeKind::Path(p) if p.visibility != usvg::Visibility::Visible => {}
             usvg::NodeKind::Path(p) => {
+                if ctx.budget.paths == 0 || ctx.buffer.vertices.len() >= ctx.budget.vertices {
+                    ctx.budget.truncated = true;
+                    return;
+                }
+                ctx.budget.paths -= 1;
+                let (first_vertex, first_index) =
+                    (ctx.buffer.vertices.len(), ctx.buffer.indices.len());
+
+                let transform = append_transform(parent_transform, p.transform);
+                if !is_finite_path(p, transform, to_egui_pos) {
+                    continue;
+                }
+                let bbox = p.data.bbox();
+                let paint_color =
+                    |svg_pos: Pos2, paint: &usvg::Paint, kind: PaintKind, opacity: f64| {
+                        let map_color = |color| {
+                            id_color.unwrap_or_else(|| {
+                                color_func(&ColorContext {
+                                    color,
+                                    kind,
+                                    id: &p.id,
+                                    opacity: opacity as f32,
+                                })
+                            })
+                        };
+                        paint_color(
+                            paint,
+                            opacity,
+                            paint_transform(paint, transform, bbox),
+                            Some(&map_color as &dyn Fn(Color32) -> Color32),
+                            svg.paint_fallback,
+                            svg_pos,
+                        )
+                    };
                 let new_egui_vertex =
                     |point: Point, paint: &usvg::Paint, opacity: f64| -> epaint::Vertex {
-                        let transform = append_transform(parent_transform, p.transform);
                         let svg_pos = {
                             let (x, y) = transform.apply(point.x as _, point.y as _);
                             Pos2::new(x as _, y as _)
                         };
-                        let egui_pos = {
-                            let mut pos = svg_pos;
-                            pos -= svg.svg_rect().min.to_vec2();
-                            pos.x *= scale.x;
-                            pos.y *= scale.y;
-                            pos += rect.min.to_vec2();
-                            pos
-                        };
                         epaint::Vertex {
-                            pos: egui_pos,
+                            pos: to_egui_pos(svg_pos),
                             uv: Pos2::ZERO,
-                            color: {
-                                match paint {
-                                    usvg::Paint::Color(c) => to_egui_color(*c, opacity),
-                                    #[cfg(feature = "gradient")]
-                                    usvg::Paint::LinearGradient(g) => {
-                                        gradient::Gradient::new(g, transform).color_at_pos(svg_pos)
-                                    }
-                                    _ => Color32::BLACK,
-                                }
-                            },
+                            color: paint_color(svg_pos, paint, PaintKind::Fill, opacity),
                         }
                     };
-                let tolerance = if svg.scale_tolerance {
-                    svg.tolerance / scale.max_elem()
-                } else {
-                    svg.tolerance
+                let (fill, stroke) = path_paints(svg, p);
+
+                #[cfg(feature = "rayon")]
+                let parallel = ctx.parallel.remove(&path_key(p));
+                #[cfg(not(feature = "rayon"))]
+                let parallel: Option<Option<PathGeometry>> = None;
+                let geometry = match &parallel {
+                    Some(geometry) => geometry.as_ref(),
+                    None => path_job(svg, scale, rect, tolerance, p, transform, fill, &stroke)
+                        .filter(|job| {
+                            job.tessellate(
+                                ctx.geometry,
+                                ctx.fill_tessellator,
+                                ctx.stroke_tessellator,
+                            )
+                        })
+                        .map(|_| &*ctx.geometry),
                 };
-                if let Some(fill) = &p.fill {
-                    fill_tesselator
-                        .tessellate(
-                            PathConvIter::new(p),
-                            &FillOptions::tolerance(tolerance),
-                            &mut BuffersBuilder::new(buffer, |f: FillVertex| {
-                                new_egui_vertex(f.position(), &fill.paint, fill.opacity.get())
-                            }),
-                        )
-                        .unwrap();
-                }
-                if let Some(stroke) = &p.stroke {
-                    stroke_tesselator
-                        .tessellate(
-                            PathConvIter::new(p),
-                            &to_lyon_stroke(stroke).with_tolerance(tolerance),
-                            &mut BuffersBuilder::new(buffer, |f: StrokeVertex| {
-                                new_egui_vertex(f.position(), &stroke.paint, stroke.opacity.get())
-                            }),
+                // skip paths lyon can't handle, like degenerate or non-finite geometry
+                let Some(geometry) = geometry else {
+                    continue;
+                };
+
+                if let Some(fill) = fill {
+                    let opacity = fill.opacity.get() * parent_opacity;
+                    ctx.buffer.vertices.extend(
+                        geometry
+                            .fill
+                            .vertices
+                            .iter()
+                            .map(|point| new_egui_vertex(*point, &fill.paint, opacity)),
+                    );
+                    ctx.buffer.indices.extend(
+                        geometry
+                            .fill
+                            .indices
+                            .iter()
+                            .map(|i| i + first_vertex as u32),
+                    );
+                    // vertex colors are interpolated linearly, so sample gradients more densely
+                    #[cfg(feature = "gradient")]
+                    if svg.gradient_subdivision > 0.0
+                        && matches!(
+                            fill.paint,
+                            usvg::Paint::LinearGradient(_) | usvg::Paint::RadialGradient(_)
                         )
-                        .unwrap();
+                    {
+                        subdivide_triangles(
+                            ctx.buffer,
+                            first_index,
+                            svg.gradient_subdivision,
+                            |pos| {
+                                paint_color(to_svg_pos(pos), &fill.paint, PaintKind::Fill, opacity)
+                            },
+                        );
+                    }
                 }
+                if let Some((stroke, _, fixed_color)) = &stroke {
+                    let first_stroke_vertex = ctx.buffer.vertices.len() as u32;
+                    ctx.buffer
+                        .vertices
+                        .extend(geometry.stroke.vertices.iter().map(|point| {
+                            let pos = Pos2::new(point.x, point.y);
+                            epaint::Vertex {
+                                pos,
+                                uv: Pos2::ZERO,
+                                color: match fixed_color {
+                                    Some(color) => scale_color(*color, parent_opacity as f32),
+                                    None => paint_color(
+                                        to_svg_pos(pos),
+                                        &stroke.paint,
+                                        PaintKind::Stroke,
+                                        stroke.opacity.get() * parent_opacity,
+                                    ),
+                                },
+                            }
+                        }));
+                    ctx.buffer.indices.extend(
+                        geometry
+                            .stroke
+                            .indices
+                            .iter()
+                            .map(|i| i + first_stroke_vertex),
+                    );
+                }
+
+                // coordinates can still overflow after transforming, e.g. with huge scales
+                if ctx.buffer.vertices[first_vertex..]
+                    .iter()
+                    .any(|v| !v.pos.is_finite())
+                {
+                    ctx.buffer.vertices.truncate(first_vertex);
+                    ctx.buffer.indices.truncate(first_index);
+                    continue;
+                }
+
+                if ctx.buffer.vertices.len() > ctx.budget.vertices {
+                    // drop the whole path so the mesh stays within the budget
+                    ctx.buffer.vertices.truncate(first_vertex);
+                    ctx.buffer.indices.truncate(first_index);
+                    ctx.budget.truncated = true;
+                    return;
+                }
+                ctx.budget.tessellated += 1;
             }
-            usvg::NodeKind::Group(g) => tessellate_recursive(
-                svg,
-                scale,
-                rect,
-                buffer,
-                fill_tesselator,
-                stroke_tesselator,
-                &node,
-                append_transform(parent_transform, g.transform),
-            ),
-            usvg::NodeKind::Image(_) | usvg::NodeKind::Text(_) => {}
+            usvg::NodeKind::Group(g) => {
+                let transform = append_transform(parent_transform, g.transform);
+                let first_index = ctx.buffer.indices.len();
+                let first_vertex = ctx.buffer.vertices.len();
+                tessellate_recursive(
+                    ctx,
+                    node.children(),
+                    transform,
+                    // group opacity is approximated by multiplying it into each element
+                    parent_opacity * g.opacity.get(),
+                    id_color,
+                );
+                if let Some(clip_path) = &g.clip_path {
+                    if let Some(polygon) = clip::clip_polygon(clip_path, transform, tolerance) {
+                        let polygon = polygon.into_iter().map(to_egui_pos).collect::<Vec<_>>();
+                        clip::clip_triangles(
+                            ctx.buffer,
+                            first_index,
+                            &polygon,
+                            &mut ctx.image_breaks,
+                        );
+                    }
+                }
+                if let Some(mask) = &g.mask {
+                    if let Some(mask) = mask::Mask::new(mask, transform, tolerance) {
+                        ctx.buffer.vertices[first_vertex..]
+                            .iter_mut()
+                            .for_each(|v| {
+                                v.color = scale_color(v.color, mask.coverage_at(to_svg_pos(v.pos)));
+                            });
+                    }
+                }
+            }
+            usvg::NodeKind::Image(_) => ctx.image_breaks.push(ctx.buffer.indices.len()),
+            usvg::NodeKind::Text(_) => {}
+        }
+    }
+}
+
+fn to_egui_pos(svg: &Svg, scale: Vec2, rect: Rect, svg_pos: Pos2) -> Pos2 {
+    let mut pos = svg_pos;
+    pos -= svg.svg_rect().min.to_vec2();
+    pos.x *= scale.x;
+    pos.y *= scale.y;
+    pos += rect.min.to_vec2();
+    pos
+}
+/// tolerance passed to lyon for paths in svg coordinates
+fn lyon_tolerance(svg: &Svg, scale: Vec2) -> f32 {
+    // lyon produces huge meshes or panics for tolerances near or below zero
+    let tolerance = svg.tolerance.max(MIN_TOLERANCE);
+    if svg.scale_tolerance {
+        tolerance / scale.max_elem()
+    } else {
+        tolerance
+    }
+}
+/// whether the path's coordinates stay finite once transformed to egui space.
+/// non-finite coordinates would smear over the whole screen
+fn is_finite_path(
+    p: &usvg::Path,
+    transform: usvg::Transform,
+    to_egui_pos: impl Fn(Pos2) -> Pos2,
+) -> bool {
+    [
+        transform.a,
+        transform.b,
+        transform.c,
+        transform.d,
+        transform.e,
+        transform.f,
+    ]
+    .iter()
+    .all(|v| v.is_finite())
+    // lyon asserts on coordinates that overflow f32 once transformed
+    && p.data.bbox().is_none_or(|b| {
+        [
+            (b.left(), b.top()),
+            (b.right(), b.top()),
+            (b.right(), b.bottom()),
+            (b.left(), b.bottom()),
+        ]
+        .into_iter()
+        .all(|(x, y)| {
+            let (x, y) = transform.apply(x, y);
+            to_egui_pos(Pos2::new(x as _, y as _)).is_finite()
+        })
+    })
+}
+
+/// the stroke to draw, its width in points if it doesn't scale with the icon
+/// and its color if it isn't painted
+type PathStroke<'a> = (
+    std::borrow::Cow<'a, usvg::Stroke>,
+    Option<f32>,
+    Option<Color32>,
+);
+
+/// the fill and stroke of a path to draw with the icon's [`PaintMode`] and stroke override
+fn path_paints<'a>(
+    svg: &Svg,
+    p: &'a usvg::Path,
+) -> (Option<&'a usvg::Fill>, Option<PathStroke<'a>>) {
+    let fill = match svg.paint_mode {
+        PaintMode::StrokeOnly | PaintMode::OutlineAll(_) => None,
+        PaintMode::FillAndStroke | PaintMode::FillOnly => p.fill.as_ref(),
+    };
+    let stroke = match (svg.paint_mode, svg.stroke_override) {
+        (PaintMode::FillOnly, _) => None,
+        (_, Some((color, width))) => Some((
+            std::borrow::Cow::Owned(usvg::Stroke {
+                linejoin: usvg::LineJoin::Round,
+                ..Default::default()
+            }),
+            Some(width),
+            Some(color),
+        )),
+        (PaintMode::FillAndStroke | PaintMode::StrokeOnly, None) => p
+            .stroke
+            .as_ref()
+            .map(|s| (std::borrow::Cow::Borrowed(s), None, None)),
+        (PaintMode::OutlineAll(width), None) => {
+            let outline = match (&p.stroke, &p.fill) {
+                (Some(stroke), _) => Some(std::borrow::Cow::Borrowed(stroke)),
+                (None, Some(fill)) => Some(std::borrow::Cow::Owned(usvg::Stroke {
+                    paint: fill.paint.clone(),
+                    opacity: fill.opacity,
+                    ..Default::default()
+                })),
+                (None, None) => None,
+            };
+            outline.map(|s| (s, Some(width), None))
+        }
+    };
+    (fill, stroke)
+}
+
+/// a path's tessellated geometry before it's colored
+#[derive(Default)]
+struct PathGeometry {
+    /// fill vertices in the path's coordinates
+    fill: VertexBuffers<Point, u32>,
+    /// stroke vertices in egui coordinates
+    stroke: VertexBuffers<Point, u32>,
+}
+
+/// everything needed to tessellate a path, copied out of the usvg tree so it's `Send`
+struct PathJob {
+    fill: Option<(lyon::path::Path, FillOptions)>,
+    /// the path is already transformed to egui coordinates
+    stroke: Option<(lyon::path::Path, StrokeOptions)>,
+}
+impl PathJob {
+    /// tessellate into `geometry`, returns `false` if lyon fails
+    fn tessellate(
+        &self,
+        geometry: &mut PathGeometry,
+        fill_tessellator: &mut FillTessellator,
+        stroke_tessellator: &mut StrokeTessellator,
+    ) -> bool {
+        for buffers in [&mut geometry.fill, &mut geometry.stroke] {
+            buffers.vertices.clear();
+            buffers.indices.clear();
+        }
+        let fill = self.fill.as_ref().is_none_or(|(path, options)| {
+            fill_tessellator
+                .tessellate_path(
+                    path,
+                    options,
+                    &mut BuffersBuilder::new(&mut geometry.fill, |f: FillVertex| f.position()),
+                )
+                .is_ok()
+        });
+        fill && self.stroke.as_ref().is_none_or(|(path, options)| {
+            stroke_tessellator
+                .tessellate_path(
+                    path,
+                    options,
+                    &mut BuffersBuilder::new(&mut geometry.stroke, |s: StrokeVertex| s.position()),
+                )
+                .is_ok()
+        })
+    }
+}
+
+/// `None` if the path isn't drawn, e.g. because of a non-finite stroke width
+#[allow(clippy::too_many_arguments)]
+fn path_job(
+    svg: &Svg,
+    scale: Vec2,
+    rect: Rect,
+    tolerance: f32,
+    p: &usvg::Path,
+    transform: usvg::Transform,
+    fill: Option<&usvg::Fill>,
+    stroke: &Option<PathStroke>,
+) -> Option<PathJob> {
+    let fill = fill.map(|fill| {
+        (
+            PathConvIter::new(p).collect(),
+            FillOptions::tolerance(tolerance).with_fill_rule(match fill.rule {
+                usvg::FillRule::NonZero => FillRule::NonZero,
+                usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+            }),
+        )
+    });
+    let stroke = match stroke {
+        Some((stroke, fixed_width, _)) => {
+            // strokes are tessellated in egui space, so a non-uniform scale
+            // stretches the path but keeps the stroke width even
+            let to_egui = lyon::math::Transform::new(
+                (transform.a * scale.x as f64) as f32,
+                (transform.b * scale.y as f64) as f32,
+                (transform.c * scale.x as f64) as f32,
+                (transform.d * scale.y as f64) as f32,
+                ((transform.e - svg.svg_rect().min.x as f64) * scale.x as f64) as f32 + rect.min.x,
+                ((transform.f - svg.svg_rect().min.y as f64) * scale.y as f64) as f32 + rect.min.y,
+            );
+            let width_factor = to_egui.determinant().abs().sqrt();
+            let line_width = fixed_width.unwrap_or(stroke.width.get() as f32 * width_factor);
+            if !line_width.is_finite() {
+                return None;
+            }
+
+            let path: Box<dyn Iterator<Item = PathEvent> + '_> = match &stroke.dasharray {
+                Some(dasharray) => Box::new(dash_path(
+                    PathConvIter::new(p).flattened(tolerance),
+                    dasharray,
+                    stroke.dashoffset,
+                )),
+                None => Box::new(PathConvIter::new(p)),
+            };
+            Some((
+                path.transformed(&to_egui).collect(),
+                to_lyon_stroke(stroke)
+                    .with_line_width(line_width)
+                    .with_tolerance(tolerance * scale.max_elem()),
+            ))
+        }
+        None => None,
+    };
+    Some(PathJob { fill, stroke })
+}
+
+/// identifies a path of the tree while it's borrowed
+#[cfg(feature = "rayon")]
+fn path_key(p: &usvg::Path) -> usize {
+    p as *const usvg::Path as usize
+}
+
+/// tessellate the geometry of all drawn paths on rayon's thread pool, keyed by [`path_key`].
+/// the geometry is colored and merged in document order by [`tessellate_recursive`], so the
+/// mesh is the same as without the `rayon` feature
+#[cfg(feature = "rayon")]
+fn tessellate_parallel(
+    svg: &Svg,
+    scale: Vec2,
+    rect: Rect,
+    nodes: &[usvg::Node],
+    transform: usvg::Transform,
+) -> std::collections::HashMap<usize, Option<PathGeometry>> {
+    use rayon::prelude::*;
+
+    fn collect_jobs(
+        svg: &Svg,
+        scale: Vec2,
+        rect: Rect,
+        nodes: impl Iterator<Item = usvg::Node>,
+        parent_transform: usvg::Transform,
+        jobs: &mut Vec<(usize, PathJob)>,
+    ) {
+        for node in nodes {
+            // paths past the budget aren't drawn anyway
+            if jobs.len() >= svg.path_budget {
+                return;
+            }
+            match &*node.borrow() {
+                usvg::NodeKind::Path(p) if p.visibility == usvg::Visibility::Visible => {
+                    let transform = append_transform(parent_transform, p.transform);
+                    if !is_finite_path(p, transform, |pos| to_egui_pos(svg, scale, rect, pos)) {
+                        continue;
+                    }
+                    let (fill, stroke) = path_paints(svg, p);
+                    let tolerance = lyon_tolerance(svg, scale);
+                    if let Some(job) =
+                        path_job(svg, scale, rect, tolerance, p, transform, fill, &stroke)
+                    {
+                        jobs.push((path_key(p), job));
+                    }
+                }
+                usvg::NodeKind::Group(g) => collect_jobs(
+                    svg,
+                    scale,
+                    rect,
+                    node.children(),
+                    append_transform(parent_transform, g.transform),
+                    jobs,
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut jobs = Vec::new();
+    collect_jobs(
+        svg,
+        scale,
+        rect,
+        nodes.iter().cloned(),
+        transform,
+        &mut jobs,
+    );
+    // small icons are faster to tessellate than to hand to the thread pool
+    if jobs.len() < PARALLEL_PATHS {
+        return Default::default();
+    }
+    jobs.into_par_iter()
+        .map_init(
+            || (FillTessellator::new(), StrokeTessellator::new()),
+            |(fill_tessellator, stroke_tessellator), (key, job)| {
+                let mut geometry = PathGeometry::default();
+                let tessellated =
+                    job.tessellate(&mut geometry, fill_tessellator, stroke_tessellator);
+                (key, tessellated.then_some(geometry))
+            },
+        )
+        .collect()
+}
+
+/// split a flattened path into dashes. dashing restarts at every sub path
+fn dash_path(
+    path: impl Iterator<Item = PathEvent>,
+    dasharray: &[f64],
+    dashoffset: f32,
+) -> std::vec::IntoIter<PathEvent> {
+    let pattern = dasharray.iter().map(|d| *d as f32).collect::<Vec<_>>();
+    let total = pattern.iter().sum::<f32>();
+    if pattern.is_empty() || total <= 0.0 || !total.is_finite() {
+        return path.collect::<Vec<_>>().into_iter();
+    }
+
+    let (start_index, start_remaining) = {
+        let mut offset = dashoffset.rem_euclid(total);
+        let mut index = 0;
+        while offset >= pattern[index] {
+            offset -= pattern[index];
+            index = (index + 1) % pattern.len();
+        }
+        (index, pattern[index] - offset)
+    };
+    let mut dasher = Dasher {
+        pattern: &pattern,
+        index: start_index,
+        remaining: start_remaining,
+        first: Point::zero(),
+        last: Point::zero(),
+        drawing: false,
+        events: Vec::new(),
+    };
+
+    for event in path {
+        match event {
+            PathEvent::Begin { .. } => {
+                dasher.end_dash();
+                dasher.index = start_index;
+                dasher.remaining = start_remaining;
+            }
+            PathEvent::Line { from, to } => dasher.line(from, to),
+            PathEvent::End { last, first, close } => {
+                if close {
+                    dasher.line(last, first);
+                }
+                dasher.end_dash();
+            }
+            // the path is flattened so there are no curves
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+        }
+    }
+    dasher.end_dash();
+
+    dasher.events.into_iter()
+}
+struct Dasher<'a> {
+    pattern: &'a [f32],
+    index: usize,
+    remaining: f32,
+    first: Point,
+    last: Point,
+    drawing: bool,
+    events: Vec<PathEvent>,
+}
+impl Dasher<'_> {
+    fn line(&mut self, from: Point, to: Point) {
+        let length = (to - from).length();
+        if length <= 0.0 {
+            return;
+        }
+        let direction = (to - from) / length;
+        let mut walked = 0.0;
+        while walked < length {
+            let step = self.remaining.min(length - walked);
+            let at = from + direction * (walked + step);
+            if self.index.is_multiple_of(2) && step > 0.0 {
+                if !self.drawing {
+                    self.first = from + direction * walked;
+                    self.last = self.first;
+                    self.events.push(PathEvent::Begin { at: self.first });
+                    self.drawing = true;
+                }
+                self.events.push(PathEvent::Line {
+                    from: self.last,
+                    to: at,
+                });
+                self.last = at;
+            }
+            walked += step;
+            self.remaining -= step;
+            if self.remaining <= 0.0 {
+                self.end_dash();
+                self.index = (self.index + 1) % self.pattern.len();
+                self.remaining = self.pattern[self.index];
+            }
+        }
+    }
+    fn end_dash(&mut self) {
+        if self.drawing {
+            self.events.push(PathEvent::End {
+                last: self.last,
+                first: self.first,
+                close: false,
+            });
+            self.drawing = false;
         }
     }
 }
 
+/// split triangles from `first_index` on until no edge is longer than `max_len`.
+/// new vertices are colored with `color_at`
+#[cfg(feature = "gradient")]
+fn subdivide_triangles(
+    buffer: &mut VertexBuffers<epaint::Vertex, u32>,
+    first_index: usize,
+    max_len: f32,
+    color_at: impl Fn(Pos2) -> Color32,
+) {
+    let mut pending = buffer
+        .indices
+        .split_off(first_index)
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect::<Vec<_>>();
+    // share midpoints between neighboring triangles to avoid cracks
+    let mut midpoints = std::collections::HashMap::new();
+
+    while let Some(triangle) = pending.pop() {
+        let pos = triangle.map(|i| buffer.vertices[i as usize].pos);
+        let (len, i) = (0..3)
+            .map(|i| ((pos[(i + 1) % 3] - pos[i]).length(), i))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap();
+        if len <= max_len || !len.is_finite() {
+            buffer.indices.extend_from_slice(&triangle);
+            continue;
+        }
+
+        let (a, b, c) = (triangle[i], triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+        let mid = *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            let pos = pos[i] + (pos[(i + 1) % 3] - pos[i]) / 2.0;
+            buffer.vertices.push(epaint::Vertex {
+                pos,
+                uv: Pos2::ZERO,
+                color: color_at(pos),
+            });
+            buffer.vertices.len() as u32 - 1
+        });
+        pending.push([a, mid, c]);
+        pending.push([mid, b, c]);
+    }
+}
+
+/// color of `paint` at `svg_pos`. `transform` is the paint's transform, see [`paint_transform`].
+/// unsupported paints use `fallback`, or the average color of a pattern's content
+pub fn paint_color(
+    paint: &usvg::Paint,
+    opacity: f64,
+    transform: usvg::Transform,
+    color_func: Option<&dyn Fn(Color32) -> Color32>,
+    fallback: Option<Color32>,
+    svg_pos: Pos2,
+) -> Color32 {
+    match paint {
+        usvg::Paint::Color(c) => to_mapped_egui_color(*c, opacity, color_func),
+        #[cfg(feature = "gradient")]
+        usvg::Paint::LinearGradient(g) => {
+            gradient::Gradient::new(g, transform, opacity, color_func).color_at_pos(svg_pos)
+        }
+        #[cfg(feature = "gradient")]
+        usvg::Paint::RadialGradient(g) => {
+            gradient::Gradient::new_radial(g, transform, opacity, color_func).color_at_pos(svg_pos)
+        }
+        _ => {
+            let _ = (transform, svg_pos);
+            match (fallback, paint) {
+                (Some(color), _) => scale_color(color, opacity as f32),
+                (None, usvg::Paint::Pattern(pattern)) => pattern_color(pattern)
+                    .map_or(Color32::BLACK, |c| {
+                        to_mapped_egui_color(c, opacity, color_func)
+                    }),
+                (None, _) => Color32::BLACK,
+            }
+        }
+    }
+}
+/// average of the solid fill colors in a pattern's content
+fn pattern_color(pattern: &usvg::Pattern) -> Option<usvg::Color> {
+    let (mut sum, mut count) = ([0u32; 3], 0);
+    for node in pattern.root.descendants() {
+        if let usvg::NodeKind::Path(p) = &*node.borrow() {
+            if let Some(usvg::Paint::Color(c)) = p.fill.as_ref().map(|f| &f.paint) {
+                sum[0] += c.red as u32;
+                sum[1] += c.green as u32;
+                sum[2] += c.blue as u32;
+                count += 1;
+            }
+        }
+    }
+    (count > 0).then(|| {
+        usvg::Color::new_rgb(
+            (sum[0] / count) as u8,
+            (sum[1] / count) as u8,
+            (sum[2] / count) as u8,
+        )
+    })
+}
+
 // https://github.com/nical/lyon/blob/f097646635a4df9d99a51f0d81b538e3c3aa1adf/examples/wgpu_svg/src/main.rs#L677
 pub struct PathConvIter<'a> {
     iter: usvg::PathSegmentsIter<'a>,
     prev: Point,
     first: Point,
-    needs_end: bool,
+    sub_path: SubPath,
     deferred: Option<PathEvent>,
 }
+/// state of the current sub path
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SubPath {
+    /// no sub path was started, or the last one has ended
+    None,
+    /// `Begin` is only emitted with the first segment, so sub paths without geometry
+    /// and consecutive move tos don't reach lyon as degenerate sub paths
+    Pending,
+    /// `Begin` was emitted and `End` is due
+    Open,
+}
 impl<'l> Iterator for PathConvIter<'l> {
     type Item = PathEvent;
     fn next(&mut self) -> Option<PathEvent> {
@@ -131,75 +985,62 @@ impl<'l> Iterator for PathConvIter<'l> {
             return self.deferred.take();
         }
 
-        let next = self.iter.next();
-        match next {
-            Some(usvg::PathSegment::MoveTo { x, y }) => {
-                if self.needs_end {
-                    let last = self.prev;
-                    let first = self.first;
-                    self.needs_end = false;
-                    self.prev = Point::new(x as f32, y as f32);
-                    self.deferred = Some(PathEvent::Begin { at: self.prev });
-                    self.first = self.prev;
-                    Some(PathEvent::End {
-                        last,
-                        first,
-                        close: false,
-                    })
-                } else {
+        loop {
+            let from = self.prev;
+            let (event, to) = match self.iter.next() {
+                Some(usvg::PathSegment::MoveTo { x, y }) => {
+                    let end = self.end(false);
                     self.first = Point::new(x as f32, y as f32);
-                    self.needs_end = true;
-                    Some(PathEvent::Begin { at: self.first })
+                    self.prev = self.first;
+                    self.sub_path = SubPath::Pending;
+                    match end {
+                        Some(end) => return Some(end),
+                        None => continue,
+                    }
                 }
-            }
-            Some(usvg::PathSegment::LineTo { x, y }) => {
-                self.needs_end = true;
-                let from = self.prev;
-                self.prev = Point::new(x as f32, y as f32);
-                Some(PathEvent::Line {
-                    from,
-                    to: self.prev,
-                })
-            }
-            Some(usvg::PathSegment::CurveTo {
-                x1,
-                y1,
-                x2,
-                y2,
-                x,
-                y,
-            }) => {
-                self.needs_end = true;
-                let from = self.prev;
-                self.prev = Point::new(x as f32, y as f32);
-                Some(PathEvent::Cubic {
-                    from,
-                    ctrl1: Point::new(x1 as f32, y1 as f32),
-                    ctrl2: Point::new(x2 as f32, y2 as f32),
-                    to: self.prev,
-                })
-            }
-            Some(usvg::PathSegment::ClosePath) => {
-                self.needs_end = false;
-                self.prev = self.first;
-                Some(PathEvent::End {
-                    last: self.prev,
-                    first: self.first,
-                    close: true,
-                })
-            }
-            None => {
-                if self.needs_end {
-                    self.needs_end = false;
-                    let last = self.prev;
-                    let first = self.first;
-                    Some(PathEvent::End {
-                        last,
-                        first,
-                        close: false,
-                    })
-                } else {
-                    None
+                Some(usvg::PathSegment::LineTo { x, y }) => {
+                    let to = Point::new(x as f32, y as f32);
+                    (PathEvent::Line { from, to }, to)
+                }
+                Some(usvg::PathSegment::CurveTo {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x,
+                    y,
+                }) => {
+                    let to = Point::new(x as f32, y as f32);
+                    let event = PathEvent::Cubic {
+                        from,
+                        ctrl1: Point::new(x1 as f32, y1 as f32),
+                        ctrl2: Point::new(x2 as f32, y2 as f32),
+                        to,
+                    };
+                    (event, to)
+                }
+                Some(usvg::PathSegment::ClosePath) => {
+                    let end = self.end(true);
+                    self.prev = self.first;
+                    match end {
+                        Some(end) => return Some(end),
+                        None => continue,
+                    }
+                }
+                None => return self.end(false),
+            };
+
+            self.prev = to;
+            match self.sub_path {
+                SubPath::Open => return Some(event),
+                SubPath::None | SubPath::Pending => {
+                    // a segment without a preceding move to starts where the last one ended
+                    if self.sub_path == SubPath::None {
+                        self.first = from;
+                    }
+                    self.sub_path = SubPath::Open;
+                    self.deferred = Some(event);
+                    return Some(PathEvent::Begin { at: self.first });
                 }
             }
         }
@@ -211,8 +1052,181 @@ impl<'l> PathConvIter<'l> {
             iter: path.data.segments(),
             first: Point::new(0.0, 0.0),
             prev: Point::new(0.0, 0.0),
+            sub_path: SubPath::None,
             deferred: None,
-            needs_end: false,
+        }
+    }
+    /// `End` of the open sub path, if any
+    fn end(&mut self, close: bool) -> Option<PathEvent> {
+        let open = self.sub_path == SubPath::Open;
+        self.sub_path = SubPath::None;
+        open.then_some(PathEvent::End {
+            last: self.prev,
+            first: self.first,
+            close,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(build: impl FnOnce(&mut usvg::PathData)) -> Vec<PathEvent> {
+        let mut data = usvg::PathData::new();
+        build(&mut data);
+        let path = usvg::Path {
+            data: std::rc::Rc::new(data),
+            ..Default::default()
+        };
+        PathConvIter::new(&path).collect()
+    }
+    fn point(x: f32, y: f32) -> Point {
+        Point::new(x, y)
+    }
+
+    #[test]
+    fn empty_sub_path() {
+        assert_eq!(
+            events(|d| {
+                d.push_move_to(0.0, 0.0);
+                d.push_close_path();
+            }),
+            []
+        );
+        assert_eq!(
+            events(|d| {
+                d.push_move_to(0.0, 0.0);
+                d.push_close_path();
+                d.push_move_to(1.0, 1.0);
+                d.push_line_to(2.0, 2.0);
+            }),
+            [
+                PathEvent::Begin {
+                    at: point(1.0, 1.0)
+                },
+                PathEvent::Line {
+                    from: point(1.0, 1.0),
+                    to: point(2.0, 2.0),
+                },
+                PathEvent::End {
+                    last: point(2.0, 2.0),
+                    first: point(1.0, 1.0),
+                    close: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn consecutive_move_tos() {
+        assert_eq!(
+            events(|d| {
+                d.push_move_to(0.0, 0.0);
+                d.push_move_to(5.0, 5.0);
+                d.push_move_to(1.0, 1.0);
+                d.push_line_to(2.0, 2.0);
+                d.push_move_to(3.0, 3.0);
+            }),
+            [
+                PathEvent::Begin {
+                    at: point(1.0, 1.0)
+                },
+                PathEvent::Line {
+                    from: point(1.0, 1.0),
+                    to: point(2.0, 2.0),
+                },
+                PathEvent::End {
+                    last: point(2.0, 2.0),
+                    first: point(1.0, 1.0),
+                    close: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn line_to_after_close_path() {
+        assert_eq!(
+            events(|d| {
+                d.push_move_to(0.0, 0.0);
+                d.push_line_to(4.0, 0.0);
+                d.push_line_to(4.0, 4.0);
+                d.push_close_path();
+                d.push_line_to(0.0, 4.0);
+            }),
+            [
+                PathEvent::Begin {
+                    at: point(0.0, 0.0)
+                },
+                PathEvent::Line {
+                    from: point(0.0, 0.0),
+                    to: point(4.0, 0.0),
+                },
+                PathEvent::Line {
+                    from: point(4.0, 0.0),
+                    to: point(4.0, 4.0),
+                },
+                PathEvent::End {
+                    last: point(4.0, 4.0),
+                    first: point(0.0, 0.0),
+                    close: true,
+                },
+                // starts at the closed sub path's start, like svg does
+                PathEvent::Begin {
+                    at: point(0.0, 0.0)
+                },
+                PathEvent::Line {
+                    from: point(0.0, 0.0),
+                    to: point(0.0, 4.0),
+                },
+                PathEvent::End {
+                    last: point(0.0, 4.0),
+                    first: point(0.0, 0.0),
+                    close: false,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_matches_serial() {
+        let paths = (0..PARALLEL_PATHS * 2)
+            .map(|i| {
+                let (x, y) = ((i % 8) * 12, (i / 8) * 12);
+                match i % 3 {
+                    0 => format!(r#"<circle cx="{x}" cy="{y}" r="5" fill="red" stroke="blue"/>"#),
+                    1 => format!(
+                        r#"<rect x="{x}" y="{y}" width="8" height="6" fill="none" stroke="green" stroke-dasharray="2 1"/>"#
+                    ),
+                    _ => format!(r#"<path d="M{x} {y}l8 0l-4 8z" fill="yellow" fill-rule="evenodd"/>"#),
+                }
+            })
+            .collect::<String>();
+        let svg = Svg::new_with_key(
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 40">
+                    <defs><clipPath id="c"><rect width="50" height="20"/></clipPath></defs>
+                    <g transform="translate(2 3)" clip-path="url(#c)">{paths}</g>
+                    {paths}
+                </svg>"#
+            )
+            .as_bytes(),
+            1,
+        );
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(200.0, 80.0));
+        let scale = Vec2::new(2.0, 2.0);
+        let (nodes, transform) = svg.root_nodes();
+        assert_eq!(
+            tessellate_parallel(&svg, scale, rect, &nodes, transform).len(),
+            PARALLEL_PATHS * 4
+        );
+        let serial = tessellate_with(&svg, rect, scale, Color32::WHITE, false);
+        for _ in 0..3 {
+            let parallel = tessellate_with(&svg, rect, scale, Color32::WHITE, true);
+            assert_eq!(parallel.mesh, serial.mesh);
+            assert_eq!(parallel.stats.paths, PARALLEL_PATHS * 4);
         }
     }
 }
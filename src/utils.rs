@@ -6,6 +6,16 @@ pub fn append_transform(mut a: usvg::Transform, b: usvg::Transform) -> usvg::Tra
     a
 }
 
+#[cfg(feature = "gradient")]
+pub fn invert_transform(t: usvg::Transform) -> usvg::Transform {
+    let det = t.a * t.d - t.b * t.c;
+    if det.abs() < f64::EPSILON {
+        return usvg::Transform::default();
+    }
+    let (a, b, c, d) = (t.d / det, -t.b / det, -t.c / det, t.a / det);
+    usvg::Transform::new(a, b, c, d, -(a * t.e + c * t.f), -(b * t.e + d * t.f))
+}
+
 pub fn to_lyon_stroke(stroke: &usvg::Stroke) -> StrokeOptions {
     let linecap = match stroke.linecap {
         usvg::LineCap::Butt => LineCap::Butt,
@@ -21,18 +31,232 @@ pub fn to_lyon_stroke(stroke: &usvg::Stroke) -> StrokeOptions {
         .with_line_width(stroke.width.get() as f32)
         .with_line_cap(linecap)
         .with_line_join(linejoin)
+        .with_miter_limit((stroke.miterlimit.get() as f32).max(StrokeOptions::MINIMUM_MITER_LIMIT))
 }
+/// vertex colors are premultiplied, so interpolating them across triangles and
+/// blending overlapping triangles doesn't darken translucent edges
 pub fn to_egui_color(color: usvg::Color, opacity: f64) -> egui::Color32 {
     egui::Color32::from_rgba_unmultiplied(
         color.red,
         color.green,
         color.blue,
-        (opacity * 255.0) as u8,
+        (opacity * 255.0).round() as u8,
+    )
+}
+/// move a premultiplied color's rgb towards `target`, keeping its alpha
+pub fn fade_color(color: egui::Color32, target: egui::Color32, t: f32) -> egui::Color32 {
+    let alpha = color.a() as f32 / 255.0;
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 * alpha - a as f32) * t).round() as u8;
+    egui::Color32::from_rgba_premultiplied(
+        mix(color.r(), target.r()),
+        mix(color.g(), target.g()),
+        mix(color.b(), target.b()),
+        color.a(),
+    )
+}
+/// scale all channels of a premultiplied color
+pub fn scale_color(color: egui::Color32, factor: f32) -> egui::Color32 {
+    let scale = |c: u8| (c as f32 * factor.clamp(0.0, 1.0)).round() as u8;
+    egui::Color32::from_rgba_premultiplied(
+        scale(color.r()),
+        scale(color.g()),
+        scale(color.b()),
+        scale(color.a()),
     )
 }
+/// invert a premultiplied color's rgb, keeping its alpha
+pub fn invert_color(color: egui::Color32) -> egui::Color32 {
+    let a = color.a();
+    egui::Color32::from_rgba_premultiplied(
+        a.saturating_sub(color.r()),
+        a.saturating_sub(color.g()),
+        a.saturating_sub(color.b()),
+        a,
+    )
+}
+/// lerp a premultiplied color towards its luminance. `amount` of 1 is fully gray
+pub fn desaturate_color(color: egui::Color32, amount: f32) -> egui::Color32 {
+    let luma = 0.2126 * color.r() as f32 + 0.7152 * color.g() as f32 + 0.0722 * color.b() as f32;
+    let mix = |c: u8| (c as f32 + (luma - c as f32) * amount.clamp(0.0, 1.0)).round() as u8;
+    egui::Color32::from_rgba_premultiplied(
+        mix(color.r()),
+        mix(color.g()),
+        mix(color.b()),
+        color.a(),
+    )
+}
+/// like [`to_egui_color`], passing the opaque color through `color_func` first
+pub fn to_mapped_egui_color(
+    color: usvg::Color,
+    opacity: f64,
+    color_func: Option<&dyn Fn(egui::Color32) -> egui::Color32>,
+) -> egui::Color32 {
+    match color_func {
+        None => to_egui_color(color, opacity),
+        Some(color_func) => {
+            let [r, g, b, a] =
+                color_func(egui::Color32::from_rgb(color.red, color.green, color.blue))
+                    .to_srgba_unmultiplied();
+            egui::Color32::from_rgba_unmultiplied(r, g, b, (a as f64 * opacity).round() as u8)
+        }
+    }
+}
+/// transform of `paint` on a path with bounding box `bbox`.
+/// gradients in `objectBoundingBox` units are stretched over the bounding box
+pub fn paint_transform(
+    paint: &usvg::Paint,
+    transform: usvg::Transform,
+    bbox: Option<usvg::PathBbox>,
+) -> usvg::Transform {
+    let units = match paint {
+        usvg::Paint::LinearGradient(g) => g.units,
+        usvg::Paint::RadialGradient(g) => g.units,
+        _ => return transform,
+    };
+    match (units, bbox) {
+        (usvg::Units::ObjectBoundingBox, Some(bbox)) => append_transform(
+            transform,
+            usvg::Transform::new(bbox.width(), 0.0, 0.0, bbox.height(), bbox.x(), bbox.y()),
+        ),
+        _ => transform,
+    }
+}
+/// byte ranges of paint and style attribute values using `currentColor` on elements
+/// without a `color` property on themselves or their ancestors
+pub fn current_color_ranges(doc: &usvg::roxmltree::Document) -> Vec<std::ops::Range<usize>> {
+    let is_set = |color: &str| !matches!(color.trim(), "currentColor" | "inherit" | "");
+    let sets_color = |node: usvg::roxmltree::Node| {
+        node.attribute("color").is_some_and(is_set)
+            || node.attribute("style").is_some_and(|style| {
+                style.split(';').any(|declaration| {
+                    let (name, value) = declaration.split_once(':').unwrap_or_default();
+                    name.trim() == "color" && is_set(value)
+                })
+            })
+    };
+
+    let text = doc.input_text();
+    let mut ranges = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        let attributes = node.attributes().filter(|a| {
+            matches!(
+                a.name(),
+                "fill" | "stroke" | "stop-color" | "flood-color" | "lighting-color" | "style"
+            ) && a.value().contains("currentColor")
+        });
+        for attribute in attributes {
+            if node.ancestors().any(sets_color) {
+                break;
+            }
+            // the value is quoted after the attribute's name
+            let start = attribute.position();
+            let rest = &text[start..];
+            let Some(open) = rest.find(['"', '\'']) else {
+                continue;
+            };
+            let quote = rest[open..].chars().next().unwrap_or('"');
+            let Some(len) = rest[open + 1..].find(quote) else {
+                continue;
+            };
+            ranges.push(start + open + 1..start + open + 1 + len);
+        }
+    }
+    ranges.sort_by_key(|r| r.start);
+    ranges
+}
+/// an opaque color that isn't written as hex color in `text`
+pub fn unused_color(text: &str) -> egui::Color32 {
+    let text = text.to_ascii_lowercase();
+    (0x01..=0xff)
+        .map(|b| egui::Color32::from_rgb(0x01, 0x02, b))
+        .find(|c| {
+            let [r, g, b, _] = c.to_array();
+            !text.contains(&format!("#{r:02x}{g:02x}{b:02x}"))
+        })
+        .unwrap_or(egui::Color32::from_rgb(0x01, 0x02, 0x03))
+}
+pub fn to_egui_align(align: usvg::Align) -> egui::Align2 {
+    match align {
+        usvg::Align::XMinYMin => egui::Align2::LEFT_TOP,
+        usvg::Align::XMidYMin => egui::Align2::CENTER_TOP,
+        usvg::Align::XMaxYMin => egui::Align2::RIGHT_TOP,
+        usvg::Align::XMinYMid => egui::Align2::LEFT_CENTER,
+        usvg::Align::None | usvg::Align::XMidYMid => egui::Align2::CENTER_CENTER,
+        usvg::Align::XMaxYMid => egui::Align2::RIGHT_CENTER,
+        usvg::Align::XMinYMax => egui::Align2::LEFT_BOTTOM,
+        usvg::Align::XMidYMax => egui::Align2::CENTER_BOTTOM,
+        usvg::Align::XMaxYMax => egui::Align2::RIGHT_BOTTOM,
+    }
+}
 pub fn to_egui_rect(rect: usvg::Rect) -> egui::Rect {
     egui::Rect::from_min_max(
         [rect.left() as f32, rect.top() as f32].into(),
         [rect.right() as f32, rect.bottom() as f32].into(),
     )
 }
+/// split `mesh` into meshes with at most `max_vertices` vertices each, keeping triangles whole
+pub fn split_mesh(mesh: egui::Mesh, max_vertices: usize) -> Vec<egui::Mesh> {
+    let max_vertices = max_vertices.max(3);
+    if mesh.vertices.len() <= max_vertices {
+        return vec![mesh];
+    }
+
+    let mut meshes = Vec::new();
+    let mut current = egui::Mesh::with_texture(mesh.texture_id);
+    let mut remap = std::collections::HashMap::new();
+    for triangle in mesh.indices.chunks_exact(3) {
+        let new_vertices = triangle.iter().filter(|i| !remap.contains_key(*i)).count();
+        if current.vertices.len() + new_vertices > max_vertices {
+            meshes.push(std::mem::replace(
+                &mut current,
+                egui::Mesh::with_texture(mesh.texture_id),
+            ));
+            remap.clear();
+        }
+        for &i in triangle {
+            let index = *remap.entry(i).or_insert_with(|| {
+                current.vertices.push(mesh.vertices[i as usize]);
+                current.vertices.len() as u32 - 1
+            });
+            current.indices.push(index);
+        }
+    }
+    if !current.indices.is_empty() {
+        meshes.push(current);
+    }
+    meshes
+}
+
+/// textures of rendered icons and embedded images. egui updates its caches once per frame,
+/// dropping the textures that weren't used since the last update like its `FrameCache`
+#[cfg(any(feature = "image", feature = "raster"))]
+#[derive(Default)]
+pub struct TextureCache {
+    generation: u32,
+    textures: std::collections::HashMap<u64, (egui::TextureHandle, u32)>,
+}
+#[cfg(any(feature = "image", feature = "raster"))]
+impl TextureCache {
+    pub fn get(&mut self, key: u64) -> Option<egui::TextureHandle> {
+        let (texture, used) = self.textures.get_mut(&key)?;
+        *used = self.generation;
+        Some(texture.clone())
+    }
+    pub fn insert(&mut self, key: u64, texture: egui::TextureHandle) {
+        self.textures.insert(key, (texture, self.generation));
+    }
+}
+#[cfg(any(feature = "image", feature = "raster"))]
+impl egui::util::cache::CacheTrait for TextureCache {
+    fn update(&mut self) {
+        let generation = self.generation;
+        self.textures.retain(|_, (_, used)| *used == generation);
+        self.generation = self.generation.wrapping_add(1);
+    }
+    fn len(&self) -> usize {
+        self.textures.len()
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}